@@ -0,0 +1,78 @@
+#![allow(non_snake_case)]
+#[macro_use]
+extern crate criterion;
+extern crate ndarray;
+extern crate ndarray_linalg;
+extern crate ndarray_rand;
+extern crate processing;
+
+use criterion::Criterion;
+use ndarray::{Array, Array2, Zip};
+use ndarray_linalg::SVD;
+use ndarray_rand::rand::{SeedableRng, rngs::StdRng};
+use ndarray_rand::rand_distr::Uniform;
+use ndarray_rand::RandomExt;
+use processing::{rPCA, rPCAHyperParams, rPCAHyperParamsBuilder, SvtSolver};
+
+/// A rank 5 matrix with 5% of its entries corrupted by large values
+fn low_rank_plus_sparse(n: usize, m: usize) -> Array2<f64> {
+    let mut rng = StdRng::seed_from_u64(42);
+    let A: Array2<f64> = Array::random_using((n, 5), Uniform::new(-1., 1.), &mut rng);
+    let B: Array2<f64> = Array::random_using((5, m), Uniform::new(-1., 1.), &mut rng);
+    let mask: Array2<f64> = Array::random_using((n, m), Uniform::new(0., 1.), &mut rng);
+    let values: Array2<f64> = Array::random_using((n, m), Uniform::new(-5., 5.), &mut rng);
+    let mut X = A.dot(&B);
+    Zip::from(&mut X).and(&mask).and(&values).apply(|x, &p, &v| {
+        if p < 0.05 {
+            *x += v;
+        }
+    });
+    X
+}
+
+fn schedules(X: &Array2<f64>) -> Vec<(&'static str, rPCAHyperParamsBuilder)> {
+    let (n, m) = X.dim();
+    let (_, sigma, _) = X.svd(false, false).unwrap();
+    vec![
+        ("fixed mu", rPCAHyperParams::new(n as u64, m as u64).fixed_mu()),
+        (
+            "adaptive mu",
+            rPCAHyperParams::new(n as u64, m as u64).mu(1.25 / sigma[0]).rho(1.5),
+        ),
+        (
+            "adaptive mu partial svt",
+            rPCAHyperParams::new(n as u64, m as u64)
+                .mu(1.25 / sigma[0])
+                .rho(1.5)
+                .svt_solver(SvtSolver::Partial),
+        ),
+    ]
+}
+
+/// Time every mu schedule. Each schedule is also fitted once outside the timed
+/// loop, which must converge, and prints a line such as
+/// `adaptive mu 100x100: 17 iterations, residual 5.8e-7` before its timings.
+fn rpca_mu_schedule(c: &mut Criterion) {
+    for &(n, m) in &[(100, 100), (300, 200)] {
+        let X = low_rank_plus_sparse(n, m);
+        for (name, builder) in schedules(&X) {
+            let hyperparams = builder.build();
+            let report = rPCA::fit(hyperparams.clone(), &X).report().clone();
+            assert!(report.converged(), "{} {}x{} did not converge", name, n, m);
+            println!(
+                "{} {}x{}: {} iterations, residual {:.1e}",
+                name, n, m, report.n_iterations(), report.residual()
+            );
+            c.bench_function(&format!("rpca {} {}x{}", name, n, m), |b| {
+                b.iter(|| rPCA::fit(hyperparams.clone(), &X))
+            });
+        }
+    }
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = rpca_mu_schedule
+}
+criterion_main!(benches);
@@ -1,3 +0,0 @@
-mod algorithm;
-
-pub use self::algorithm::*;
\ No newline at end of file
@@ -0,0 +1,115 @@
+use crate::float::{Element, Float};
+use ndarray::{ArrayBase, Data, Ix2};
+use ndarray_linalg::error::LinalgError;
+use std::error;
+use std::fmt;
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+/// Errors returned by the fallible methods of this crate.
+///
+/// Every method of the estimators and builders that panics on invalid input,
+/// `build`, `fit`, `partial_fit`, `transform`, `inverse_transform` and
+/// `decompose` and their variants, delegates to a `try_` counterpart
+/// returning this error instead.
+#[derive(Debug)]
+pub enum Error {
+    /// The input has no samples or no features, or too few samples to estimate a variance
+    EmptyInput,
+    /// The input contains NaN or infinite values
+    NonFiniteValue,
+    /// A hyperparameter failed its validation checks
+    InvalidHyperParameter(String),
+    /// The LAPACK decomposition did not succeed
+    Svd(LinalgError),
+    /// A linear system could not be solved, its matrix being singular
+    LinearSolve(LinalgError),
+    /// More components were requested than min(n_samples, n_features)
+    TooManyComponents { requested: usize, max: usize },
+    /// The input does not have the number of features the model was fitted on
+    FeatureMismatch { expected: usize, found: usize },
+    /// The scores do not have the number of components the model retained
+    ComponentMismatch { expected: usize, found: usize },
+    /// The observation mask does not have the shape of the input
+    MaskShape { expected: (usize, usize), found: (usize, usize) },
+    /// A model could not be encoded or decoded
+    Serialization(String),
+    /// A saved model was written with an unsupported format version
+    FormatVersion { expected: u32, found: u32 },
+    /// An incrementally fitted model was used before seeing any sample
+    NotFitted,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::EmptyInput => write!(f, "the input has too few samples or features"),
+            Error::NonFiniteValue => write!(f, "the input contains NaN or infinite values"),
+            Error::InvalidHyperParameter(msg) => write!(f, "invalid hyperparameter: {}", msg),
+            Error::Svd(err) => write!(f, "SVD failed: {}", err),
+            Error::LinearSolve(err) => write!(f, "solving a linear system failed: {}", err),
+            Error::TooManyComponents { requested, max } => write!(
+                f,
+                "cannot keep {} components, at most min(n_samples, n_features) = {} are available",
+                requested, max
+            ),
+            Error::FeatureMismatch { expected, found } => write!(
+                f,
+                "expected an input with {} features, found {}",
+                expected, found
+            ),
+            Error::ComponentMismatch { expected, found } => write!(
+                f,
+                "expected scores with {} components, found {}",
+                expected, found
+            ),
+            Error::MaskShape { expected, found } => write!(
+                f,
+                "expected a mask of shape {:?}, found {:?}",
+                expected, found
+            ),
+            Error::Serialization(msg) => write!(f, "serialization failed: {}", msg),
+            Error::FormatVersion { expected, found } => write!(
+                f,
+                "the model was saved with format version {}, only version {} can be loaded",
+                found, expected
+            ),
+            Error::NotFitted => write!(f, "the model has not seen any sample yet"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Svd(err) | Error::LinearSolve(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<LinalgError> for Error {
+    fn from(error: LinalgError) -> Error {
+        Error::Svd(error)
+    }
+}
+
+/// Check that `x` has at least `min_samples` rows, at least one column and
+/// only finite values.
+pub(crate) fn check_input<A, R>(
+    x: &ArrayBase<impl Data<Elem = A>, Ix2>,
+    min_samples: usize,
+) -> Result<()>
+where
+    A: Element<Real = R>,
+    R: Float,
+{
+    let (n, m) = x.dim();
+    if n == 0 || m == 0 || n < min_samples {
+        return Err(Error::EmptyInput);
+    }
+    if x.iter().any(|v| !(v.re().is_finite() && v.im().is_finite())) {
+        return Err(Error::NonFiniteValue);
+    }
+    Ok(())
+}
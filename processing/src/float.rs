@@ -0,0 +1,23 @@
+use ndarray::{NdFloat, ScalarOperand};
+use ndarray_linalg::{c32, c64, Lapack, Scalar};
+
+/// The element types accepted by `PCA`, the real `Float`s and the complex
+/// `c32` and `c64`. `Scalar::Real` is the type of the variances.
+pub trait Element: Scalar + Lapack + ScalarOperand {}
+
+impl Element for f32 {}
+
+impl Element for f64 {}
+
+impl Element for c32 {}
+
+impl Element for c64 {}
+
+/// The real floating point types accepted by the estimators, `f32` and `f64`.
+///
+/// Computations run in the precision of the input, the hyperparameters stay `f64`.
+pub trait Float: Element + NdFloat + Scalar<Real = Self> {}
+
+impl Float for f32 {}
+
+impl Float for f64 {}
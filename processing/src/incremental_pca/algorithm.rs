@@ -0,0 +1,350 @@
+#![allow(non_snake_case, non_camel_case_types)]
+use ndarray::{stack, ArrayBase, Array2, Array1, Axis, Data, Ix2, s};
+use ndarray_linalg::SVD;
+use crate::error::{check_input, Error, Result};
+use crate::incremental_pca::hyperparameters::IncrementalPCAHyperParams;
+
+/// PCA fitted one mini-batch at a time, following the incremental SVD of
+/// Ross, Lim, Lin & Yang, "Incremental learning for robust visual tracking", 2008.
+///
+/// Only the retained components, their singular values and the running mean and
+/// variance are kept between batches, so the memory footprint does not depend on
+/// the number of samples seen.
+pub struct IncrementalPCA {
+    /// principal axes in feature space, shape (n_components, n_features)
+    components: Array2<f64>,
+    mean : Array1<f64>,
+    /// running sum of squared deviations from the mean of every feature
+    squared_deviations: Array1<f64>,
+    explained_variance: Array1<f64>,
+    explained_variance_ratio: Array1<f64>,
+    singular_values: Array1<f64>,
+    n_samples_seen: usize,
+    hyperparameters: IncrementalPCAHyperParams,
+}
+
+impl IncrementalPCA {
+
+    /// An empty model, to be fitted with `partial_fit`.
+    pub fn new(hyperparameters: IncrementalPCAHyperParams) -> Self {
+        IncrementalPCA {
+            components: Array2::zeros((0, 0)),
+            mean: Array1::zeros(0),
+            squared_deviations: Array1::zeros(0),
+            explained_variance: Array1::zeros(0),
+            explained_variance_ratio: Array1::zeros(0),
+            singular_values: Array1::zeros(0),
+            n_samples_seen: 0,
+            hyperparameters,
+        }
+    }
+
+    /// Update the model with `batch`, of shape (n_samples, n_features).
+    ///
+    /// **Panics** if `try_partial_fit` returns an error.
+    pub fn partial_fit (
+        &mut self,
+        batch : &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) {
+        if let Err(err) = self.try_partial_fit(batch) {
+            panic!("{}", err)
+        }
+    }
+
+    /// Update the model with `batch`, of shape (n_samples, n_features).
+    ///
+    /// Fails if `batch` has non-finite values or a different number of features
+    /// than the previous batches, if the first batch has fewer than two samples
+    /// or fewer samples than `n_components`, or if the SVD fails. The model is
+    /// left untouched on failure.
+    pub fn try_partial_fit (
+        &mut self,
+        batch : &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> Result<()> {
+        let first = self.n_samples_seen == 0;
+        check_input(batch, if first { 2 } else { 1 })?;
+        let (_n, _m) = batch.dim();
+        let k = self.hyperparameters.n_components;
+        if first {
+            if k > _n.min(_m) {
+                return Err(Error::TooManyComponents { requested: k, max: _n.min(_m) });
+            }
+        } else if _m != self.n_features() {
+            return Err(Error::FeatureMismatch { expected: self.n_features(), found: _m });
+        }
+
+        // update the running mean and variance, Chan et al. pairwise formulas
+        let batch_mean = batch.mean_axis(Axis(0)).ok_or(Error::EmptyInput)?;
+        let centered = batch - &batch_mean;
+        let batch_squared_deviations = centered.map(|x| x.powi(2)).sum_axis(Axis(0));
+        let seen = self.n_samples_seen as f64;
+        let n_total = self.n_samples_seen + _n;
+        let (mean, squared_deviations) = if first {
+            (batch_mean.clone(), batch_squared_deviations)
+        } else {
+            let delta = &batch_mean - &self.mean;
+            let mean = &self.mean + &(&delta * (_n as f64 / n_total as f64));
+            let correction = delta.map(|x| x.powi(2)) * (seen * _n as f64 / n_total as f64);
+            (mean, &self.squared_deviations + &batch_squared_deviations + correction)
+        };
+
+        // the previous components, weighted by their singular values, summarize the
+        // samples already seen; the extra row accounts for the shift of the mean
+        let b = if first {
+            centered
+        } else {
+            let previous = &self.components * &self.singular_values.view().insert_axis(Axis(1));
+            let shift = (&self.mean - &batch_mean) * (seen * _n as f64 / n_total as f64).sqrt();
+            stack(Axis(0), &[previous.view(), centered.view(), shift.view().insert_axis(Axis(0))])
+                .expect("every block has n_features columns")
+        };
+
+        let (_, sigma, v) = b.svd(false, true)?;
+        let v = v.expect("V^T was requested from the SVD");
+
+        let temp = (n_total - 1) as f64;
+        let total_var = squared_deviations.sum() / temp;
+        let explained_variance = sigma.slice(s![..k]).map(|x| x.powi(2) / temp);
+
+        self.explained_variance_ratio = explained_variance.map(|x| x / total_var);
+        self.explained_variance = explained_variance;
+        self.components = v.slice(s![..k, ..]).to_owned();
+        self.singular_values = sigma.slice(s![..k]).to_owned();
+        self.mean = mean;
+        self.squared_deviations = squared_deviations;
+        self.n_samples_seen = n_total;
+        Ok(())
+    }
+
+    /// Project `x` onto the retained principal axes, after centering it with the
+    /// running mean. Returns an array of shape (n_samples, n_components).
+    ///
+    /// When whitening is enabled each projection is divided by the square root
+    /// of the explained variance of its component.
+    ///
+    /// **Panics** if `try_transform` returns an error.
+    pub fn transform (
+        &self,
+        x : &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> Array2<f64> {
+        match self.try_transform(x) {
+            Ok(z) => z,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Project `x` onto the retained principal axes, after centering it with the
+    /// running mean. Returns an array of shape (n_samples, n_components).
+    ///
+    /// Fails if called before `partial_fit` or if `x` does not have the number
+    /// of features of the batches.
+    pub fn try_transform (
+        &self,
+        x : &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> Result<Array2<f64>> {
+        if self.n_samples_seen == 0 {
+            return Err(Error::NotFitted);
+        }
+        if x.ncols() != self.n_features() {
+            return Err(Error::FeatureMismatch { expected: self.n_features(), found: x.ncols() });
+        }
+        let centered = x - &self.mean;
+        let z = centered.dot(&self.components.t());
+        if self.hyperparameters.whiten {
+            Ok(z / &self.explained_variance.map(|x| x.sqrt()))
+        } else {
+            Ok(z)
+        }
+    }
+
+    /// Map scores produced by `transform` back to the original feature space.
+    ///
+    /// **Panics** if `try_inverse_transform` returns an error.
+    pub fn inverse_transform (
+        &self,
+        z : &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> Array2<f64> {
+        match self.try_inverse_transform(z) {
+            Ok(x) => x,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Map scores produced by `transform` back to the original feature space.
+    ///
+    /// Fails if called before `partial_fit` or if `z` does not have one column
+    /// per retained component.
+    pub fn try_inverse_transform (
+        &self,
+        z : &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> Result<Array2<f64>> {
+        if self.n_samples_seen == 0 {
+            return Err(Error::NotFitted);
+        }
+        let n_components = self.components.nrows();
+        if z.ncols() != n_components {
+            return Err(Error::ComponentMismatch { expected: n_components, found: z.ncols() });
+        }
+        if self.hyperparameters.whiten {
+            let z = z * &self.explained_variance.map(|x| x.sqrt());
+            Ok(z.dot(&self.components) + &self.mean)
+        } else {
+            Ok(z.dot(&self.components) + &self.mean)
+        }
+    }
+
+    /// The running mean of every feature.
+    pub fn mean(&self) -> &Array1<f64> {
+        &self.mean
+    }
+
+    /// The principal axes as rows, shape (n_components, n_features).
+    pub fn components(&self) -> &Array2<f64> {
+        &self.components
+    }
+
+    /// Variance of the samples seen so far along each retained principal axis.
+    pub fn explained_variance(&self) -> &Array1<f64> {
+        &self.explained_variance
+    }
+
+    /// Fraction of the total variance explained by each retained principal axis.
+    pub fn explained_variance_ratio(&self) -> &Array1<f64> {
+        &self.explained_variance_ratio
+    }
+
+    /// Singular values of the centered samples seen so far for each retained component.
+    pub fn singular_values(&self) -> &Array1<f64> {
+        &self.singular_values
+    }
+
+    /// Total variance of the samples seen so far, summed over every feature.
+    pub fn total_variance(&self) -> f64 {
+        if self.n_samples_seen < 2 {
+            return 0.0;
+        }
+        self.squared_deviations.sum() / (self.n_samples_seen - 1) as f64
+    }
+
+    /// The number of samples seen by `partial_fit` so far.
+    pub fn n_samples_seen(&self) -> usize {
+        self.n_samples_seen
+    }
+
+    /// The number of features of the batches, 0 before the first `partial_fit`.
+    pub fn n_features(&self) -> usize {
+        self.mean.len()
+    }
+
+    /// The hyperparameters of the model.
+    pub fn hyperparameters(&self) -> &IncrementalPCAHyperParams {
+        &self.hyperparameters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pca::{Components, PCA, PCAHyperParams, SvdSolver};
+    use ndarray::{Array, Array2};
+    use ndarray_rand::RandomExt;
+    use ndarray_rand::rand_distr::Uniform;
+
+    fn fit_in_batches(hyperparameters: IncrementalPCAHyperParams, X: &Array2<f64>, batch_size: usize) -> IncrementalPCA {
+        let mut model = IncrementalPCA::new(hyperparameters);
+        for batch in X.axis_chunks_iter(Axis(0), batch_size) {
+            model.partial_fit(&batch);
+        }
+        model
+    }
+
+    #[test]
+    fn test_IncrementalPCA_matches_PCA() {
+        // keeping every component the incremental SVD is exact
+        let X: Array2<f64> = Array::random((230, 6), Uniform::new(-10., 10.)) + 3.0;
+        let model = fit_in_batches(IncrementalPCAHyperParams::new(6).build(), &X, 50);
+        let batch = PCA::fit(PCAHyperParams::new(Components::All).solver(SvdSolver::Full).build(), &X);
+
+        assert_eq!(model.n_samples_seen(), 230);
+        assert_eq!(model.n_features(), 6);
+        assert!((model.mean() - batch.mean()).iter().all(|x| x.abs() < 1e-10));
+        assert!((model.total_variance() - batch.total_variance()).abs() < 1e-8);
+        for k in 0..6 {
+            let rel = (model.explained_variance()[k] - batch.explained_variance()[k]) / batch.explained_variance()[k];
+            assert!(rel.abs() < 1e-8);
+            assert!((model.explained_variance_ratio()[k] - batch.explained_variance_ratio()[k]).abs() < 1e-8);
+            let cosine: f64 = model.components().row(k).iter()
+                .zip(batch.components().row(k).iter())
+                .map(|(a, b)| a * b)
+                .sum();
+            assert!((cosine.abs() - 1.0).abs() < 1e-8);
+        }
+
+        // and the projection is lossless
+        let Z = model.transform(&X);
+        assert_eq!(Z.dim(), (230, 6));
+        assert!((model.inverse_transform(&Z) - &X).iter().all(|x| x.abs() < 1e-8));
+    }
+
+    #[test]
+    fn test_IncrementalPCA_low_rank() {
+        // truncating between batches is harmless when the data has few dominant directions
+        let W: Array2<f64> = Array::random((3, 40), Uniform::new(-1., 1.));
+        let H: Array2<f64> = Array::random((400, 3), Uniform::new(-10., 10.));
+        let noise: Array2<f64> = Array::random((400, 40), Uniform::new(-0.01, 0.01));
+        let X = H.dot(&W) + noise;
+
+        let model = fit_in_batches(IncrementalPCAHyperParams::new(3).whiten(true).build(), &X, 64);
+        let batch = PCA::fit(PCAHyperParams::new(Components::Count(3)).whiten(true).build(), &X);
+
+        for k in 0..3 {
+            let rel = (model.singular_values()[k] - batch.singular_values()[k]) / batch.singular_values()[k];
+            assert!(rel.abs() < 1e-4);
+        }
+        // compare the projectors, which do not depend on the sign of the axes
+        let projector = model.components().t().dot(model.components());
+        let expected = batch.components().t().dot(batch.components());
+        assert!((projector - expected).iter().all(|x| x.abs() < 1e-4));
+
+        let reconstruction = model.inverse_transform(&model.transform(&X));
+        assert!((reconstruction - &X).iter().all(|x| x.abs() < 0.1));
+    }
+
+    #[test]
+    fn test_IncrementalPCA_errors() {
+        let X: Array2<f64> = Array::random((10, 4), Uniform::new(-1., 1.));
+
+        let mut model = IncrementalPCA::new(IncrementalPCAHyperParams::new(5).build());
+        assert!(matches!(
+            model.try_partial_fit(&X),
+            Err(Error::TooManyComponents { requested: 5, max: 4 })
+        ));
+        assert_eq!(model.n_samples_seen(), 0);
+
+        let mut model = IncrementalPCA::new(IncrementalPCAHyperParams::new(2).build());
+        assert!(matches!(model.try_transform(&X), Err(Error::NotFitted)));
+        assert!(matches!(model.try_inverse_transform(&Array2::zeros((3, 2))), Err(Error::NotFitted)));
+        assert!(matches!(model.try_partial_fit(&X.slice(s![..1, ..])), Err(Error::EmptyInput)));
+        model.partial_fit(&X);
+        assert!(matches!(
+            model.try_transform(&X.slice(s![.., ..3])),
+            Err(Error::FeatureMismatch { expected: 4, found: 3 })
+        ));
+        assert!(matches!(
+            model.try_inverse_transform(&Array2::zeros((3, 4))),
+            Err(Error::ComponentMismatch { expected: 2, found: 4 })
+        ));
+        assert!(matches!(
+            model.try_partial_fit(&X.slice(s![.., ..3])),
+            Err(Error::FeatureMismatch { expected: 4, found: 3 })
+        ));
+        // later batches may hold a single sample
+        model.partial_fit(&X.slice(s![..1, ..]));
+        assert_eq!(model.n_samples_seen(), 11);
+
+        assert!(matches!(
+            IncrementalPCAHyperParams::new(0).try_build(),
+            Err(Error::InvalidHyperParameter(_))
+        ));
+    }
+}
@@ -0,0 +1,80 @@
+#![allow(non_snake_case, non_camel_case_types)]
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IncrementalPCAHyperParams {
+    /// n_components - number of principal components to keep
+    pub(crate) n_components: usize,
+    /// whiten - scale the projections to unit variance, default = false
+    pub(crate) whiten: bool,
+}
+
+/// An helper struct used to construct a set of [valid hyperparameters]
+pub struct IncrementalPCAHyperParamsBuilder {
+    n_components: usize,
+    whiten: bool,
+}
+
+impl IncrementalPCAHyperParamsBuilder {
+
+    /// Set the value of `whiten`.
+    ///
+    /// When set, the projections returned by `transform` are divided by the
+    /// square root of the explained variance of each component
+    pub fn whiten(mut self, whiten: bool) -> Self {
+        self.whiten = whiten;
+        self
+    }
+
+    /// Return an instance of `IncrementalPCAHyperParams` after
+    /// having performed validation checks on all the specified hyperparamters.
+    ///
+    /// **Panics** if any of the validation checks fails.
+    pub fn build(self) -> IncrementalPCAHyperParams {
+        match self.try_build() {
+            Ok(hyperparameters) => hyperparameters,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Return an instance of `IncrementalPCAHyperParams` after
+    /// having performed validation checks on all the specified hyperparamters,
+    /// or the first check that failed.
+    pub fn try_build(self) -> Result<IncrementalPCAHyperParams> {
+        IncrementalPCAHyperParams::build(self.n_components, self.whiten)
+    }
+}
+
+impl IncrementalPCAHyperParams {
+    /// `new` lets us configure our incremental PCA parameters:
+    /// * `n_components` principal components are kept between batches.
+    ///
+    /// `n_components` is mandatory.
+    ///
+    /// Defaults are provided if optional parameters are not specified:
+    /// * `whiten = false`.
+    pub fn new(n_components: usize) -> IncrementalPCAHyperParamsBuilder {
+        IncrementalPCAHyperParamsBuilder {
+            n_components,
+            whiten: false,
+        }
+    }
+
+    /// The number of principal components kept between batches.
+    pub fn n_components(&self) -> usize { self.n_components }
+
+    /// Whether projections are scaled to unit variance.
+    pub fn whiten(&self) -> bool { self.whiten }
+
+    fn build(n_components: usize, whiten: bool) -> Result<Self> {
+        // the shape dependent checks happen in `partial_fit`
+        if n_components == 0 {
+            return Err(Error::InvalidHyperParameter("`n_components` cannot be 0!".to_string()));
+        }
+        Ok(IncrementalPCAHyperParams {
+            n_components,
+            whiten,
+        })
+    }
+}
@@ -2,4 +2,4 @@ mod algorithm;
 mod hyperparameters;
 
 pub use self::algorithm::*;
-pub use self::hyperparameters::*;
\ No newline at end of file
+pub use self::hyperparameters::*;
@@ -0,0 +1,292 @@
+#![allow(non_snake_case, non_camel_case_types)]
+use ndarray::{ArrayBase, Array2, Array1, Axis, Data, Ix2, s};
+use ndarray_linalg::{Eigh, Inverse, UPLO};
+use crate::error::{check_input, Error, Result};
+use crate::kernel_pca::hyperparameters::KernelPCAHyperParams;
+use crate::kernel_pca::kernels::{kernel_matrix, Kernel};
+
+/// Kernel PCA, Schölkopf, Smola & Müller, "Nonlinear component analysis as a
+/// kernel eigenvalue problem", 1998.
+///
+/// The principal components are the leading eigenvectors of the centered kernel
+/// matrix of the training samples, which are kept to project new samples.
+pub struct KernelPCA<K: Kernel> {
+    /// the training samples, shape (n_samples, n_features)
+    x_fit: Array2<f64>,
+    /// eigenvectors of the centered kernel matrix, shape (n_samples, n_components)
+    eigenvectors: Array2<f64>,
+    eigenvalues: Array1<f64>,
+    /// projection of the training samples onto the principal components
+    scores: Array2<f64>,
+    /// column means of the uncentered training kernel matrix
+    kernel_means: Array1<f64>,
+    kernel_mean: f64,
+    /// coefficients of the kernel ridge regression from scores to samples
+    dual_coef: Array2<f64>,
+    hyperparameters: KernelPCAHyperParams<K>,
+}
+
+impl<K: Kernel> KernelPCA<K> {
+
+    /// Fit the model on `x`, of shape (n_samples, n_features), keeping
+    /// `n_components` principal components.
+    ///
+    /// **Panics** if `try_fit` returns an error.
+    pub fn fit (
+        hyperparameters: KernelPCAHyperParams<K>,
+        x : &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> Self {
+        match KernelPCA::try_fit(hyperparameters, x) {
+            Ok(model) => model,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Fit the model on `x`, of shape (n_samples, n_features), keeping
+    /// `n_components` principal components.
+    ///
+    /// Fails if `x` has fewer than two samples or non-finite values, if the
+    /// centered kernel matrix has fewer than `n_components` positive eigenvalues
+    /// or if a LAPACK routine fails.
+    pub fn try_fit (
+        hyperparameters: KernelPCAHyperParams<K>,
+        x : &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> Result<Self> {
+        check_input(x, 2)?;
+        let _n = x.nrows();
+        let k = hyperparameters.n_components;
+        if k > _n {
+            return Err(Error::TooManyComponents { requested: k, max: _n });
+        }
+
+        // center the kernel matrix in feature space
+        let kernel = kernel_matrix(&hyperparameters.kernel, x, x);
+        let kernel_means = kernel.mean_axis(Axis(0)).ok_or(Error::EmptyInput)?;
+        let kernel_mean = kernel_means.mean().ok_or(Error::EmptyInput)?;
+        let centered = center(&kernel, &kernel_means, &kernel_means, kernel_mean);
+
+        // eigh returns ascending eigenvalues, flip them
+        let (w, v) = centered.eigh(UPLO::Upper)?;
+        let w = w.slice(s![..;-1]).to_owned();
+        let v = v.slice(s![.., ..;-1]).to_owned();
+
+        // the directions of vanishing variance cannot be normalized
+        let cutoff = w[0].max(0.0) * _n as f64 * f64::EPSILON;
+        let n_positive = w.iter().filter(|&&l| l > cutoff).count();
+        if k > n_positive {
+            return Err(Error::TooManyComponents { requested: k, max: n_positive });
+        }
+        let eigenvalues = w.slice(s![..k]).to_owned();
+        let eigenvectors = v.slice(s![.., ..k]).to_owned();
+        let scores = &eigenvectors * &eigenvalues.map(|l| l.sqrt());
+
+        // learn the pre-image map, a kernel ridge regression from scores to samples
+        let regularized = kernel_matrix(&hyperparameters.kernel, &scores, &scores)
+            + Array2::<f64>::eye(_n) * hyperparameters.alpha;
+        let dual_coef = regularized.inv().map_err(Error::LinearSolve)?.dot(x);
+
+        Ok(Self {
+            x_fit: x.to_owned(),
+            eigenvectors,
+            eigenvalues,
+            scores,
+            kernel_means,
+            kernel_mean,
+            dual_coef,
+            hyperparameters,
+        })
+    }
+
+    /// Project `x` onto the principal components through its kernel with the
+    /// training samples. Returns an array of shape (n_samples, n_components).
+    ///
+    /// **Panics** if `try_transform` returns an error.
+    pub fn transform (
+        &self,
+        x : &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> Array2<f64> {
+        match self.try_transform(x) {
+            Ok(z) => z,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Project `x` onto the principal components through its kernel with the
+    /// training samples. Returns an array of shape (n_samples, n_components).
+    ///
+    /// Fails if `x` does not have the number of features seen during `fit`.
+    pub fn try_transform (
+        &self,
+        x : &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> Result<Array2<f64>> {
+        let n_features = self.x_fit.ncols();
+        if x.ncols() != n_features {
+            return Err(Error::FeatureMismatch { expected: n_features, found: x.ncols() });
+        }
+        let kernel = kernel_matrix(&self.hyperparameters.kernel, x, &self.x_fit);
+        let row_means = kernel.mean_axis(Axis(1)).expect("the training set is not empty");
+        let centered = center(&kernel, &row_means, &self.kernel_means, self.kernel_mean);
+        Ok(centered.dot(&self.eigenvectors) / &self.eigenvalues.map(|l| l.sqrt()))
+    }
+
+    /// Approximate pre-images in feature space of scores produced by `transform`,
+    /// through the kernel ridge regression learned on the training scores.
+    ///
+    /// **Panics** if `try_inverse_transform` returns an error.
+    pub fn inverse_transform (
+        &self,
+        z : &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> Array2<f64> {
+        match self.try_inverse_transform(z) {
+            Ok(x) => x,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Approximate pre-images in feature space of scores produced by `transform`,
+    /// through the kernel ridge regression learned on the training scores.
+    ///
+    /// Fails if `z` does not have one column per retained component.
+    pub fn try_inverse_transform (
+        &self,
+        z : &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> Result<Array2<f64>> {
+        let n_components = self.eigenvalues.len();
+        if z.ncols() != n_components {
+            return Err(Error::ComponentMismatch { expected: n_components, found: z.ncols() });
+        }
+        Ok(kernel_matrix(&self.hyperparameters.kernel, z, &self.scores).dot(&self.dual_coef))
+    }
+
+    /// Eigenvectors of the centered kernel matrix as columns, shape (n_samples, n_components).
+    pub fn eigenvectors(&self) -> &Array2<f64> {
+        &self.eigenvectors
+    }
+
+    /// Eigenvalues of the centered kernel matrix for each retained component.
+    pub fn eigenvalues(&self) -> &Array1<f64> {
+        &self.eigenvalues
+    }
+
+    /// The training samples projected onto the principal components, shape (n_samples, n_components).
+    pub fn scores(&self) -> &Array2<f64> {
+        &self.scores
+    }
+
+    /// The hyperparameters used during `fit`.
+    pub fn hyperparameters(&self) -> &KernelPCAHyperParams<K> {
+        &self.hyperparameters
+    }
+}
+
+/// Center a kernel matrix in feature space, given the means of its rows, the column
+/// means and the overall mean of the training kernel matrix
+fn center(
+    kernel: &Array2<f64>,
+    row_means: &Array1<f64>,
+    training_means: &Array1<f64>,
+    training_mean: f64,
+) -> Array2<f64> {
+    kernel - training_means - row_means.view().insert_axis(Axis(1)) + training_mean
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel_pca::kernels::{Cosine, Linear, Polynomial, Rbf, Sigmoid};
+    use crate::pca::{Components, PCA, PCAHyperParams};
+    use ndarray::{Array, Array2, ArrayView1};
+    use ndarray_rand::RandomExt;
+    use ndarray_rand::rand_distr::Uniform;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_KernelPCA_linear_kernel_is_PCA() {
+        let X: Array2<f64> = Array::random((40, 5), Uniform::new(-10., 10.));
+        let model = KernelPCA::fit(KernelPCAHyperParams::new(Linear, 3).build(), &X);
+        let pca = PCA::fit(PCAHyperParams::new(Components::Count(3)).build(), &X);
+
+        // the eigenvalues of the centered gram matrix are the squared singular values
+        for k in 0..3 {
+            let rel = (model.eigenvalues()[k] - pca.singular_values()[k].powi(2)) / model.eigenvalues()[k];
+            assert!(rel.abs() < 1e-8);
+        }
+        // and the scores match up to the sign of each component
+        let difference = model.scores().map(|x| x.abs()) - pca.scores().map(|x| x.abs());
+        assert!(difference.iter().all(|x| x.abs() < 1e-6));
+
+        // a user-defined kernel is used as is
+        let dot = |x: ArrayView1<f64>, y: ArrayView1<f64>| x.iter().zip(y.iter()).map(|(a, b)| a * b).sum::<f64>();
+        let custom = KernelPCA::fit(KernelPCAHyperParams::new(dot, 3).build(), &X);
+        assert!((custom.eigenvalues() - model.eigenvalues()).iter().all(|x| x.abs() < 1e-6));
+    }
+
+    /// Projecting the training samples must give back the training scores
+    fn check_transform<K: Kernel>(kernel: K, n_components: usize, X: &Array2<f64>) {
+        let model = KernelPCA::fit(KernelPCAHyperParams::new(kernel, n_components).build(), X);
+        let projected = model.transform(X);
+        assert_eq!(projected.dim(), (X.nrows(), n_components));
+        assert!((model.scores() - &projected).iter().all(|x| x.abs() < 1e-8));
+    }
+
+    #[test]
+    fn test_KernelPCA_transform() {
+        let X: Array2<f64> = Array::random((50, 4), Uniform::new(-1., 1.));
+        check_transform(Rbf { gamma: 0.5 }, 4, &X);
+        check_transform(Polynomial { gamma: 1.0, coef0: 1.0, degree: 3 }, 4, &X);
+        check_transform(Sigmoid { gamma: 0.1, coef0: 0.0 }, 2, &X);
+        check_transform(Cosine, 3, &X);
+    }
+
+    #[test]
+    fn test_KernelPCA_inverse_transform() {
+        // noisy samples of a circle, which the RBF components unroll
+        let angles: Array1<f64> = Array::random(200, Uniform::new(0., 2.0 * PI));
+        let noise: Array2<f64> = Array::random((200, 2), Uniform::new(-0.05, 0.05));
+        let mut X = Array2::zeros((200, 2));
+        X.column_mut(0).assign(&angles.map(|a| a.cos()));
+        X.column_mut(1).assign(&angles.map(|a| a.sin()));
+        X = X + noise;
+
+        let hyperparams = KernelPCAHyperParams::new(Rbf { gamma: 2.0 }, 8).alpha(1e-3).build();
+        let model = KernelPCA::fit(hyperparams, &X);
+        let reconstruction = model.inverse_transform(&model.transform(&X));
+
+        // the pre-images are much closer to the samples than their mean is
+        let error = (&reconstruction - &X).map(|x| x.powi(2)).sum();
+        let baseline = (&X - &X.mean_axis(Axis(0)).unwrap()).map(|x| x.powi(2)).sum();
+        assert!(error < 0.05 * baseline);
+    }
+
+    #[test]
+    fn test_KernelPCA_errors() {
+        let X: Array2<f64> = Array::random((10, 3), Uniform::new(-1., 1.));
+
+        // a linear kernel on 3 features has at most 3 non-zero eigenvalues
+        assert!(matches!(
+            KernelPCA::try_fit(KernelPCAHyperParams::new(Linear, 5).build(), &X),
+            Err(Error::TooManyComponents { requested: 5, max: 3 })
+        ));
+        assert!(matches!(
+            KernelPCA::try_fit(KernelPCAHyperParams::new(Linear, 11).build(), &X),
+            Err(Error::TooManyComponents { requested: 11, max: 10 })
+        ));
+
+        // new samples and scores must have the width seen during fit
+        let model = KernelPCA::fit(KernelPCAHyperParams::new(Rbf { gamma: 1.0 }, 2).build(), &X);
+        assert!(matches!(
+            model.try_transform(&X.slice(s![.., ..2])),
+            Err(Error::FeatureMismatch { expected: 3, found: 2 })
+        ));
+        assert!(matches!(
+            model.try_inverse_transform(&Array2::zeros((4, 3))),
+            Err(Error::ComponentMismatch { expected: 2, found: 3 })
+        ));
+        assert_eq!(model.try_transform(&X).unwrap().dim(), (10, 2));
+
+        assert!(matches!(
+            KernelPCAHyperParams::new(Linear, 2).alpha(0.0).try_build(),
+            Err(Error::InvalidHyperParameter(_))
+        ));
+    }
+}
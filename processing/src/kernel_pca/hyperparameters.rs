@@ -0,0 +1,93 @@
+#![allow(non_snake_case, non_camel_case_types)]
+use crate::error::{Error, Result};
+use crate::kernel_pca::kernels::Kernel;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KernelPCAHyperParams<K: Kernel> {
+    /// kernel - similarity between two samples
+    pub(crate) kernel: K,
+    /// n_components - number of principal components to keep
+    pub(crate) n_components: usize,
+    /// alpha - ridge penalty of the learned pre-image map, default = 1
+    pub(crate) alpha: f64,
+}
+
+/// An helper struct used to construct a set of [valid hyperparameters]
+pub struct KernelPCAHyperParamsBuilder<K: Kernel> {
+    kernel: K,
+    n_components: usize,
+    alpha: f64,
+}
+
+impl<K: Kernel> KernelPCAHyperParamsBuilder<K> {
+
+    /// Set the value of `alpha`.
+    ///
+    /// `inverse_transform` maps scores back to feature space with a kernel ridge
+    /// regression learned on the training scores, alpha is its ridge penalty
+    pub fn alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Return an instance of `KernelPCAHyperParams` after
+    /// having performed validation checks on all the specified hyperparamters.
+    ///
+    /// **Panics** if any of the validation checks fails.
+    pub fn build(self) -> KernelPCAHyperParams<K> {
+        match self.try_build() {
+            Ok(hyperparameters) => hyperparameters,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Return an instance of `KernelPCAHyperParams` after
+    /// having performed validation checks on all the specified hyperparamters,
+    /// or the first check that failed.
+    pub fn try_build(self) -> Result<KernelPCAHyperParams<K>> {
+        KernelPCAHyperParams::build(self.kernel, self.n_components, self.alpha)
+    }
+}
+
+impl<K: Kernel> KernelPCAHyperParams<K> {
+    /// `new` lets us configure our kernel PCA parameters:
+    /// * `kernel` measures the similarity between samples;
+    /// * `n_components` principal components are kept by `fit`.
+    ///
+    /// `kernel` and `n_components` are mandatory.
+    ///
+    /// Defaults are provided if optional parameters are not specified:
+    /// * `alpha = 1`.
+    pub fn new(kernel: K, n_components: usize) -> KernelPCAHyperParamsBuilder<K> {
+        KernelPCAHyperParamsBuilder {
+            kernel,
+            n_components,
+            alpha: 1.0,
+        }
+    }
+
+    /// The similarity between two samples.
+    pub fn kernel(&self) -> &K { &self.kernel }
+
+    /// The number of principal components kept by `fit`.
+    pub fn n_components(&self) -> usize { self.n_components }
+
+    /// The ridge penalty of the learned pre-image map.
+    pub fn alpha(&self) -> f64 { self.alpha }
+
+    fn build(kernel: K, n_components: usize, alpha: f64) -> Result<Self> {
+        // the shape dependent checks happen in `fit`
+        if n_components == 0 {
+            return Err(Error::InvalidHyperParameter("`n_components` cannot be 0!".to_string()));
+        }
+        if !alpha.is_finite() || alpha <= 0.0 {
+            return Err(Error::InvalidHyperParameter("`alpha` must be greater than 0!".to_string()));
+        }
+        Ok(KernelPCAHyperParams {
+            kernel,
+            n_components,
+            alpha,
+        })
+    }
+}
@@ -0,0 +1,102 @@
+use ndarray::{ArrayBase, Array2, ArrayView1, Data, Ix2};
+use serde::{Deserialize, Serialize};
+
+/// A positive semi-definite similarity between two samples, used by `KernelPCA`.
+///
+/// Any `Fn(ArrayView1<f64>, ArrayView1<f64>) -> f64` closure is a kernel.
+pub trait Kernel {
+    fn compute(&self, x: ArrayView1<f64>, y: ArrayView1<f64>) -> f64;
+}
+
+impl<F> Kernel for F
+where
+    F: Fn(ArrayView1<f64>, ArrayView1<f64>) -> f64,
+{
+    fn compute(&self, x: ArrayView1<f64>, y: ArrayView1<f64>) -> f64 {
+        self(x, y)
+    }
+}
+
+/// k(x, y) = x . y, which makes `KernelPCA` equivalent to `PCA`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Linear;
+
+impl Kernel for Linear {
+    fn compute(&self, x: ArrayView1<f64>, y: ArrayView1<f64>) -> f64 {
+        dot(x, y)
+    }
+}
+
+/// k(x, y) = exp(-gamma ||x - y||^2)
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rbf {
+    pub gamma: f64,
+}
+
+impl Kernel for Rbf {
+    fn compute(&self, x: ArrayView1<f64>, y: ArrayView1<f64>) -> f64 {
+        let distance: f64 = x.iter().zip(y.iter()).map(|(a, b)| (a - b).powi(2)).sum();
+        (-self.gamma * distance).exp()
+    }
+}
+
+/// k(x, y) = (gamma x . y + coef0)^degree
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Polynomial {
+    pub gamma: f64,
+    pub coef0: f64,
+    pub degree: i32,
+}
+
+impl Kernel for Polynomial {
+    fn compute(&self, x: ArrayView1<f64>, y: ArrayView1<f64>) -> f64 {
+        (self.gamma * dot(x, y) + self.coef0).powi(self.degree)
+    }
+}
+
+/// k(x, y) = tanh(gamma x . y + coef0), only positive semi-definite for some parameters
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Sigmoid {
+    pub gamma: f64,
+    pub coef0: f64,
+}
+
+impl Kernel for Sigmoid {
+    fn compute(&self, x: ArrayView1<f64>, y: ArrayView1<f64>) -> f64 {
+        (self.gamma * dot(x, y) + self.coef0).tanh()
+    }
+}
+
+/// k(x, y) = x . y / (||x|| ||y||), 0 if either sample is zero
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Cosine;
+
+impl Kernel for Cosine {
+    fn compute(&self, x: ArrayView1<f64>, y: ArrayView1<f64>) -> f64 {
+        let norms = (dot(x.view(), x.view()) * dot(y.view(), y.view())).sqrt();
+        if norms > 0.0 {
+            dot(x, y) / norms
+        } else {
+            0.0
+        }
+    }
+}
+
+fn dot(x: ArrayView1<f64>, y: ArrayView1<f64>) -> f64 {
+    x.iter().zip(y.iter()).map(|(a, b)| a * b).sum()
+}
+
+/// The kernel between every row of `a` and every row of `b`, shape (a.nrows(), b.nrows())
+pub(crate) fn kernel_matrix<K: Kernel>(
+    kernel: &K,
+    a: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    b: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+) -> Array2<f64> {
+    let mut out = Array2::zeros((a.nrows(), b.nrows()));
+    for (i, x) in a.outer_iter().enumerate() {
+        for (j, y) in b.outer_iter().enumerate() {
+            out[[i, j]] = kernel.compute(x, y);
+        }
+    }
+    out
+}
@@ -0,0 +1,7 @@
+mod algorithm;
+mod hyperparameters;
+mod kernels;
+
+pub use self::algorithm::*;
+pub use self::hyperparameters::*;
+pub use self::kernels::*;
@@ -2,11 +2,34 @@ extern crate ndarray;
 extern crate ndarray_linalg;
 extern crate ndarray_rand;
 extern crate serde;
+extern crate serde_json;
+extern crate bincode;
+extern crate sprs;
 
 
+mod error;
+mod float;
 #[allow(clippy::new_ret_no_self)]
-mod PCA;
-mod rPCA;
+mod incremental_pca;
+#[allow(clippy::new_ret_no_self)]
+mod kernel_pca;
+mod linalg;
+#[allow(clippy::new_ret_no_self)]
+mod online_rpca;
+mod persistence;
+#[allow(clippy::new_ret_no_self)]
+mod pca;
+#[allow(clippy::new_ret_no_self)]
+mod rpca;
+#[allow(clippy::new_ret_no_self)]
+mod sparse_pca;
 
-pub use PCA::*;
-pub use rPCA::*;
\ No newline at end of file
+pub use error::{Error, Result};
+pub use float::{Element, Float};
+pub use incremental_pca::*;
+pub use kernel_pca::*;
+pub use online_rpca::*;
+pub use pca::*;
+pub use persistence::{Persist, FORMAT_VERSION};
+pub use rpca::*;
+pub use sparse_pca::*;
@@ -0,0 +1,185 @@
+use crate::error::Result;
+use crate::float::{Element, Float};
+use ndarray::{Array, Array1, Array2, ArrayBase, Axis, Data, Ix2, ShapeBuilder, Zip, s};
+use ndarray_linalg::{Eigh, QR, SVD, UPLO};
+use ndarray_rand::rand::Rng;
+use ndarray_rand::rand_distr::StandardNormal;
+use ndarray_rand::RandomExt;
+use sprs::CsMatBase;
+use std::ops::Deref;
+
+/// A matrix only accessed through its products with dense blocks of vectors.
+pub(crate) trait LinearOperator<A: Element> {
+    /// The shape (n, m) of the matrix.
+    fn dim(&self) -> (usize, usize);
+
+    /// The product a b of the matrix with `b`, of shape (m, l).
+    fn dot(&self, b: &Array2<A>) -> Array2<A>;
+
+    /// The product a^H b of the conjugate transpose of the matrix with `b`, of shape (n, l).
+    fn dot_h(&self, b: &Array2<A>) -> Array2<A>;
+}
+
+impl<A: Element, S: Data<Elem = A>> LinearOperator<A> for ArrayBase<S, Ix2> {
+    fn dim(&self) -> (usize, usize) {
+        ArrayBase::dim(self)
+    }
+
+    fn dot(&self, b: &Array2<A>) -> Array2<A> {
+        ndarray::linalg::Dot::dot(self, b)
+    }
+
+    fn dot_h(&self, b: &Array2<A>) -> Array2<A> {
+        conjugate_transpose(self).dot(b)
+    }
+}
+
+/// The sparse matrix x minus the mean of its rows, x - 1 mean^T, applied without
+/// forming the dense centered matrix: a b = x b - 1 (mean^T b) and
+/// a^H b = x^H b - conj(mean) (1^T b).
+pub(crate) struct CenteredSparse<'a, A: Element, IpS, IS, DS>
+where
+    IpS: Deref<Target = [usize]>,
+    IS: Deref<Target = [usize]>,
+    DS: Deref<Target = [A]>,
+{
+    pub(crate) x: &'a CsMatBase<A, usize, IpS, IS, DS>,
+    pub(crate) mean: &'a Array1<A>,
+}
+
+impl<'a, A, IpS, IS, DS> LinearOperator<A> for CenteredSparse<'a, A, IpS, IS, DS>
+where
+    A: Element,
+    IpS: Deref<Target = [usize]>,
+    IS: Deref<Target = [usize]>,
+    DS: Deref<Target = [A]>,
+{
+    fn dim(&self) -> (usize, usize) {
+        (self.x.rows(), self.x.cols())
+    }
+
+    fn dot(&self, b: &Array2<A>) -> Array2<A> {
+        let mut out = Array2::zeros((self.x.rows(), b.ncols()));
+        // iter yields (row, column) for both storage orders
+        for (&v, (i, j)) in self.x.iter() {
+            out.row_mut(i).scaled_add(v, &b.row(j));
+        }
+        out - &self.mean.dot(b)
+    }
+
+    fn dot_h(&self, b: &Array2<A>) -> Array2<A> {
+        let mut out = Array2::zeros((self.x.cols(), b.ncols()));
+        for (&v, (i, j)) in self.x.iter() {
+            out.row_mut(j).scaled_add(v.conj(), &b.row(i));
+        }
+        let sums = b.sum_axis(Axis(0));
+        Zip::from(out.genrows_mut()).and(self.mean).apply(|mut row, &mean| {
+            row.scaled_add(-mean.conj(), &sums);
+        });
+        out
+    }
+}
+
+/// Truncated SVD of `a` through a randomized range finder, Halko, Martinsson & Tropp,
+/// "Finding structure with randomness", 2011 (algorithms 4.4 and 5.1).
+///
+/// Returns U (n x k), the k leading singular values and V^T (k x m). The range
+/// is sampled with `k + n_oversamples` gaussian vectors and refined by
+/// `n_power_iterations` re-orthonormalized power iterations. `a` is only
+/// touched through its products, so it may be a sparse or implicit operator.
+pub(crate) fn randomized_svd<A, R, G>(
+    a: &impl LinearOperator<A>,
+    k: usize,
+    n_oversamples: usize,
+    n_power_iterations: usize,
+    rng: &mut G,
+) -> Result<(Array2<A>, Array1<R>, Array2<A>)>
+where
+    A: Element<Real = R>,
+    R: Float,
+    G: Rng,
+{
+    let (n, m) = a.dim();
+    let l = (k + n_oversamples).min(n.min(m));
+
+    // sample the range of a
+    let omega: Array2<f64> = Array::random_using((m, l), StandardNormal, rng);
+    let omega = omega.mapv(|x| A::from_real(A::real(x)));
+    let (mut q, _) = a.dot(&omega).qr()?;
+    for _ in 0..n_power_iterations {
+        let (z, _) = a.dot_h(&q).qr()?;
+        let (y, _) = a.dot(&z).qr()?;
+        q = y;
+    }
+
+    // project a on the range, b = q^H a = (a^H q)^H, and decompose the small matrix
+    let b = conjugate_transpose(&a.dot_h(&q));
+    let (ub, sigma, vt) = b.svd(true, true)?;
+    let ub = ub.expect("U was requested from the SVD");
+    let vt = vt.expect("V^T was requested from the SVD");
+    let k = k.min(sigma.len());
+
+    let u = q.dot(&ub.slice(s![.., ..k]));
+    Ok((u, sigma.slice(s![..k]).to_owned(), vt.slice(s![..k, ..]).to_owned()))
+}
+
+/// Thin SVD of `a` through the Hermitian eigendecomposition of the smaller of
+/// its two cross products, a^H a (m x m) for tall matrices and the Gram matrix
+/// a a^H (n x n) for wide ones.
+///
+/// Returns U (n x r), the r = min(n, m) singular values in decreasing order and
+/// V^H (r x m). Forming the cross product squares the condition number, so the
+/// singular vectors of vanishing singular values cannot be recovered from `a`;
+/// they are replaced by an orthonormal completion of the others.
+pub(crate) fn eigh_svd<A, R>(
+    a: &ArrayBase<impl Data<Elem = A>, Ix2>,
+) -> Result<(Array2<A>, Array1<R>, Array2<A>)>
+where
+    A: Element<Real = R>,
+    R: Float,
+{
+    let (n, m) = a.dim();
+    let tall = n >= m;
+    let a_h = conjugate_transpose(a);
+    let cross = if tall { a_h.dot(a) } else { a.dot(&a_h) };
+    // eigh transposes row-major inputs, which conjugates a Hermitian matrix and
+    // its eigenvectors, so hand it a column-major copy
+    let mut cross_f = Array2::zeros(cross.dim().f());
+    cross_f.assign(&cross);
+
+    // eigh returns ascending eigenvalues, flip them into the SVD order
+    let (w, vectors) = cross_f.eigh(UPLO::Upper)?;
+    let sigma = w.slice(s![..;-1]).map(|x| x.max(R::zero()).sqrt());
+    let vectors = vectors.slice(s![.., ..;-1]).to_owned();
+
+    // the eigenvalues carry an absolute error of about eps * sigma_max^2, so the
+    // smaller singular values are indistinguishable from round-off
+    let cutoff = sigma[0] * (R::real(n.max(m)) * R::epsilon()).sqrt();
+    let inverse = sigma.map(|&x| A::from_real(if x > cutoff { x.recip() } else { R::zero() }));
+
+    let rank = sigma.iter().take_while(|&&x| x > cutoff).count();
+    if tall {
+        let u = complete_columns(a.dot(&vectors) * &inverse, rank)?;
+        Ok((u, sigma, conjugate_transpose(&vectors)))
+    } else {
+        let v = complete_columns(a_h.dot(&vectors) * &inverse, rank)?;
+        Ok((vectors, sigma, conjugate_transpose(&v)))
+    }
+}
+
+/// Replace the columns of `q` past the first `rank`, which are orthonormal,
+/// by orthonormal vectors spanning the complement of the leading ones.
+fn complete_columns<A: Element>(mut q: Array2<A>, rank: usize) -> Result<Array2<A>> {
+    if rank < q.ncols() {
+        // Householder QR keeps the span of the leading columns and returns
+        // orthonormal columns even where `q` is zero
+        let (basis, _) = q.qr()?;
+        q.slice_mut(s![.., rank..]).assign(&basis.slice(s![.., rank..]));
+    }
+    Ok(q)
+}
+
+/// The conjugate transpose a^H of `a`, its plain transpose for real elements.
+pub(crate) fn conjugate_transpose<A: Element>(a: &ArrayBase<impl Data<Elem = A>, Ix2>) -> Array2<A> {
+    a.t().mapv(|x| x.conj())
+}
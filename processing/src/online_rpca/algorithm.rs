@@ -0,0 +1,259 @@
+#![allow(non_snake_case, non_camel_case_types)]
+use ndarray::{ArrayBase, Array, Array1, Array2, Axis, Data, Ix1};
+use ndarray_linalg::Inverse;
+use ndarray_rand::RandomExt;
+use ndarray_rand::rand::{SeedableRng, rngs::StdRng};
+use ndarray_rand::rand_distr::StandardNormal;
+use crate::error::{check_input, Error, Result};
+use crate::online_rpca::hyperparameters::OnlineRPCAHyperParams;
+use crate::rpca::So;
+
+/// Online robust PCA through stochastic optimization, Feng, Xu & Yan,
+/// "Online Robust PCA via Stochastic Optimization", NIPS 2013.
+///
+/// Every sample z is decomposed as `basis * r + e`, with coefficients r and a
+/// sparse outlier vector e, and the basis is then refined from the accumulated
+/// statistics of all the samples seen, whose number does not affect the memory used.
+pub struct OnlineRPCA {
+    /// low-rank basis, shape (n_features, rank)
+    basis: Array2<f64>,
+    /// accumulated r r^T, shape (rank, rank)
+    A: Array2<f64>,
+    /// accumulated (z - e) r^T, shape (n_features, rank)
+    B: Array2<f64>,
+    n_samples_seen: usize,
+    hyperparameters: OnlineRPCAHyperParams,
+}
+
+impl OnlineRPCA {
+
+    /// A model with a random initial basis, to be fitted with `partial_fit`.
+    pub fn new(hyperparameters: OnlineRPCAHyperParams) -> Self {
+        let (p, r) = (hyperparameters.n_features, hyperparameters.rank);
+        let mut rng = StdRng::seed_from_u64(hyperparameters.random_seed);
+        let basis = Array::random_using((p, r), StandardNormal, &mut rng);
+        OnlineRPCA {
+            basis,
+            A: Array2::zeros((r, r)),
+            B: Array2::zeros((p, r)),
+            n_samples_seen: 0,
+            hyperparameters,
+        }
+    }
+
+    /// Update the model with `sample`, returning its sparse outlier vector.
+    ///
+    /// **Panics** if `try_partial_fit` returns an error.
+    pub fn partial_fit (
+        &mut self,
+        sample : &ArrayBase<impl Data<Elem = f64>, Ix1>,
+    ) -> Array1<f64> {
+        match self.try_partial_fit(sample) {
+            Ok(outliers) => outliers,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Update the model with `sample`, returning its sparse outlier vector.
+    ///
+    /// Fails if `sample` has non-finite values or not `n_features` entries,
+    /// or with `Error::LinearSolve` if the coefficient system cannot be
+    /// inverted. The model is left untouched on failure.
+    pub fn try_partial_fit (
+        &mut self,
+        sample : &ArrayBase<impl Data<Elem = f64>, Ix1>,
+    ) -> Result<Array1<f64>> {
+        let (r, e) = self.solve(sample)?;
+
+        // accumulate the statistics of the surrogate function
+        let r_col = r.view().insert_axis(Axis(1));
+        self.A = &self.A + &r_col.dot(&r_col.t());
+        let clean = (sample - &e).insert_axis(Axis(1));
+        self.B = &self.B + &clean.dot(&r_col.t());
+
+        // block coordinate descent on the basis, one column at a time
+        let A = &self.A + &(Array2::<f64>::eye(self.hyperparameters.rank) * self.hyperparameters.ridge);
+        for j in 0..self.hyperparameters.rank {
+            let step = (&self.B.column(j) - &self.basis.dot(&A.column(j))) / A[[j, j]];
+            let mut column = self.basis.column_mut(j);
+            column += &step;
+        }
+        self.n_samples_seen += 1;
+        Ok(e)
+    }
+
+    /// Decompose `sample` on the current basis without updating the model,
+    /// returning its low-rank part and its sparse outlier vector.
+    ///
+    /// **Panics** if `try_decompose` returns an error.
+    pub fn decompose (
+        &self,
+        sample : &ArrayBase<impl Data<Elem = f64>, Ix1>,
+    ) -> (Array1<f64>, Array1<f64>) {
+        match self.try_decompose(sample) {
+            Ok(parts) => parts,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Decompose `sample` on the current basis without updating the model,
+    /// returning its low-rank part and its sparse outlier vector.
+    ///
+    /// Fails if `sample` has non-finite values or not `n_features` entries, or
+    /// with `Error::LinearSolve` if the coefficient system cannot be inverted.
+    pub fn try_decompose (
+        &self,
+        sample : &ArrayBase<impl Data<Elem = f64>, Ix1>,
+    ) -> Result<(Array1<f64>, Array1<f64>)> {
+        let (r, e) = self.solve(sample)?;
+        Ok((self.basis.dot(&r), e))
+    }
+
+    /// Solve for the coefficients r and the outliers e of `sample`, alternating
+    /// the ridge regression for r and the soft-thresholding of the residual for e
+    fn solve (
+        &self,
+        sample : &ArrayBase<impl Data<Elem = f64>, Ix1>,
+    ) -> Result<(Array1<f64>, Array1<f64>)> {
+        let row = sample.view().insert_axis(Axis(0));
+        check_input(&row, 1)?;
+        if sample.len() != self.hyperparameters.n_features {
+            return Err(Error::FeatureMismatch {
+                expected: self.hyperparameters.n_features,
+                found: sample.len(),
+            });
+        }
+
+        let rank = self.hyperparameters.rank;
+        let gram = self.basis.t().dot(&self.basis) + Array2::<f64>::eye(rank) * self.hyperparameters.ridge;
+        let projection = gram.inv().map_err(Error::LinearSolve)?.dot(&self.basis.t());
+
+        let scale = sample.map(|x| x.powi(2)).sum().sqrt().max(f64::MIN_POSITIVE);
+        let mut r = Array1::zeros(rank);
+        let mut e = Array1::zeros(sample.len());
+        for _ in 0..self.hyperparameters.max_n_iterations {
+            let new_r = projection.dot(&(sample - &e));
+            let residual = sample - &self.basis.dot(&new_r);
+            let new_e = So(self.hyperparameters.lambda, &residual.insert_axis(Axis(0))).index_axis_move(Axis(0), 0);
+
+            let change = (&new_r - &r).map(|x| x.powi(2)).sum() + (&new_e - &e).map(|x| x.powi(2)).sum();
+            r = new_r;
+            e = new_e;
+            if change.sqrt() < self.hyperparameters.tolerance * scale {
+                break;
+            }
+        }
+        Ok((r, e))
+    }
+
+    /// The low-rank basis, shape (n_features, rank). Its columns span the
+    /// subspace of the samples but are neither normalized nor orthogonal.
+    pub fn basis(&self) -> &Array2<f64> {
+        &self.basis
+    }
+
+    /// The number of samples seen by `partial_fit` so far.
+    pub fn n_samples_seen(&self) -> usize {
+        self.n_samples_seen
+    }
+
+    /// The hyperparameters of the model.
+    pub fn hyperparameters(&self) -> &OnlineRPCAHyperParams {
+        &self.hyperparameters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{Array, Array2};
+    use ndarray_linalg::SVD;
+    use ndarray_rand::rand::Rng;
+    use ndarray_rand::rand_distr::Uniform;
+
+    /// Orthogonal projector on the column space of `M`
+    fn projector(M: &Array2<f64>, rank: usize) -> Array2<f64> {
+        let (u, _, _) = M.svd(true, false).unwrap();
+        let u = u.unwrap();
+        let u = u.slice(ndarray::s![.., ..rank]);
+        u.dot(&u.t())
+    }
+
+    #[test]
+    fn test_OnlineRPCA_recovers_subspace_and_outliers() {
+        let (p, rank) = (30, 2);
+        let mut rng = StdRng::seed_from_u64(5);
+        let U: Array2<f64> = Array::random_using((p, rank), Uniform::new(-1., 1.), &mut rng);
+
+        let mut model = OnlineRPCA::new(OnlineRPCAHyperParams::new(p, rank).build());
+        let (mut detected, mut corrupted, mut false_alarms) = (0, 0, 0);
+        for t in 0..3000 {
+            let coefficients: Array1<f64> = Array::random_using(rank, Uniform::new(-1., 1.), &mut rng);
+            let mut sample = U.dot(&coefficients);
+            let mut truth = Array1::zeros(p);
+            for i in 0..p {
+                if rng.gen::<f64>() < 0.05 {
+                    truth[i] = if rng.gen::<bool>() { 5.0 } else { -5.0 };
+                }
+            }
+            sample += &truth;
+
+            let outliers = model.partial_fit(&sample);
+            assert_eq!(outliers.len(), p);
+            if t >= 2000 {
+                for i in 0..p {
+                    if truth[i] != 0.0 {
+                        corrupted += 1;
+                        if outliers[i] * truth[i] > 1.0 {
+                            detected += 1;
+                        }
+                    } else if outliers[i].abs() > 1.0 {
+                        false_alarms += 1;
+                    }
+                }
+            }
+        }
+        assert_eq!(model.n_samples_seen(), 3000);
+
+        // the basis spans the low-rank subspace
+        let distance = projector(model.basis(), rank) - projector(&U, rank);
+        assert!(distance.iter().all(|x| x.abs() < 0.05));
+
+        // and the outliers of the later samples are found
+        assert!(detected as f64 > 0.95 * corrupted as f64);
+        assert!(false_alarms < corrupted / 20);
+
+        // decomposing a clean sample leaves no outliers
+        let clean = U.dot(&Array1::from(vec![0.5, -0.3]));
+        let (low_rank, outliers) = model.decompose(&clean);
+        assert!(outliers.iter().all(|x| x.abs() < 0.1));
+        assert!((low_rank - &clean).iter().all(|x| x.abs() < 0.1));
+    }
+
+    #[test]
+    fn test_OnlineRPCA_errors() {
+        let mut model = OnlineRPCA::new(OnlineRPCAHyperParams::new(4, 2).build());
+        assert!(matches!(
+            model.try_partial_fit(&Array1::<f64>::zeros(3)),
+            Err(Error::FeatureMismatch { expected: 4, found: 3 })
+        ));
+        assert!(matches!(
+            model.try_partial_fit(&Array1::from(vec![1.0, f64::NAN, 0.0, 0.0])),
+            Err(Error::NonFiniteValue)
+        ));
+        assert!(matches!(
+            model.try_decompose(&Array1::<f64>::zeros(5)),
+            Err(Error::FeatureMismatch { expected: 4, found: 5 })
+        ));
+        assert_eq!(model.n_samples_seen(), 0);
+
+        assert!(matches!(
+            OnlineRPCAHyperParams::new(4, 5).try_build(),
+            Err(Error::TooManyComponents { requested: 5, max: 4 })
+        ));
+        assert!(matches!(
+            OnlineRPCAHyperParams::new(4, 2).lambda(0.0).try_build(),
+            Err(Error::InvalidHyperParameter(_))
+        ));
+    }
+}
@@ -0,0 +1,192 @@
+#![allow(non_snake_case, non_camel_case_types)]
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OnlineRPCAHyperParams {
+    /// n_features - dimension of every sample
+    pub(crate) n_features: usize,
+    /// rank - number of columns of the low-rank basis
+    pub(crate) rank: usize,
+    /// lambda - penalty on the sparse outliers, default = 1/sqrt(n_features)
+    pub(crate) lambda: f64,
+    /// ridge - penalty on the coefficients and the basis, default = 1/sqrt(n_features)
+    pub(crate) ridge: f64,
+    /// tolerance - convergence tolerance of the per sample solve, default = 1e-6
+    pub(crate) tolerance: f64,
+    /// max_n_iterations - maximum number of iterations of the per sample solve, default = 100
+    pub(crate) max_n_iterations: u64,
+    /// random_seed - seed of the initial basis, default = 0
+    pub(crate) random_seed: u64,
+}
+
+/// An helper struct used to construct a set of [valid hyperparameters]
+pub struct OnlineRPCAHyperParamsBuilder {
+    n_features: usize,
+    rank: usize,
+    lambda: f64,
+    ridge: f64,
+    tolerance: f64,
+    max_n_iterations: u64,
+    random_seed: u64,
+}
+
+impl OnlineRPCAHyperParamsBuilder {
+
+    /// Set the value of `lambda`.
+    ///
+    /// Lambda weighs the l1 norm of the sparse outlier vector of every
+    /// sample, as in `rPCAHyperParams`
+    pub fn lambda(mut self, lambda: f64) -> Self {
+        self.lambda = lambda;
+        self
+    }
+
+    /// Set the value of `ridge`.
+    ///
+    /// The squared l2 penalty on the coefficients of every sample, which
+    /// also regularizes the basis, lambda_1 in the paper
+    pub fn ridge(mut self, ridge: f64) -> Self {
+        self.ridge = ridge;
+        self
+    }
+
+    /// Set the value of `tolerance`.
+    ///
+    /// The alternating solve for the coefficients and outliers of a sample
+    /// stops once their update is smaller than `tolerance` times the norm of the sample
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Set the value of `max_n_iterations`.
+    ///
+    /// Upper bound on the iterations of the alternating solve of every sample
+    pub fn max_n_iterations(mut self, max_n_iterations: u64) -> Self {
+        self.max_n_iterations = max_n_iterations;
+        self
+    }
+
+    /// Set the value of `random_seed`.
+    ///
+    /// The seed makes the random initial basis reproducible
+    pub fn random_seed(mut self, random_seed: u64) -> Self {
+        self.random_seed = random_seed;
+        self
+    }
+
+    /// Return an instance of `OnlineRPCAHyperParams` after
+    /// having performed validation checks on all the specified hyperparamters.
+    ///
+    /// **Panics** if any of the validation checks fails.
+    pub fn build(self) -> OnlineRPCAHyperParams {
+        match self.try_build() {
+            Ok(hyperparameters) => hyperparameters,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Return an instance of `OnlineRPCAHyperParams` after
+    /// having performed validation checks on all the specified hyperparamters,
+    /// or the first check that failed.
+    pub fn try_build(self) -> Result<OnlineRPCAHyperParams> {
+        OnlineRPCAHyperParams::build(
+            self.n_features,
+            self.rank,
+            self.lambda,
+            self.ridge,
+            self.tolerance,
+            self.max_n_iterations,
+            self.random_seed,
+        )
+    }
+}
+
+impl OnlineRPCAHyperParams {
+    /// `new` lets us configure our online robust PCA parameters:
+    /// * every sample has `n_features` entries;
+    /// * the low-rank part of the samples lies in a subspace of dimension `rank`.
+    ///
+    /// `n_features` and `rank` are mandatory.
+    ///
+    /// Defaults are provided if optional parameters are not specified:
+    /// * `lambda = 1/sqrt(n_features)`;
+    /// * `ridge = 1/sqrt(n_features)`;
+    /// * `tolerance = 1e-6`;
+    /// * `max_n_iterations = 100`;
+    /// * `random_seed = 0`.
+    pub fn new(n_features: usize, rank: usize) -> OnlineRPCAHyperParamsBuilder {
+        OnlineRPCAHyperParamsBuilder {
+            n_features,
+            rank,
+            lambda: 1.0 / (n_features as f64).sqrt(),
+            ridge: 1.0 / (n_features as f64).sqrt(),
+            tolerance: 1.0e-6,
+            max_n_iterations: 100,
+            random_seed: 0,
+        }
+    }
+
+    /// The dimension of every sample.
+    pub fn n_features(&self) -> usize { self.n_features }
+
+    /// The dimension of the low-rank subspace.
+    pub fn rank(&self) -> usize { self.rank }
+
+    /// The penalty on the sparse outliers.
+    pub fn lambda(&self) -> f64 { self.lambda }
+
+    /// The penalty on the coefficients and the basis.
+    pub fn ridge(&self) -> f64 { self.ridge }
+
+    /// The convergence tolerance of the per sample solve.
+    pub fn tolerance(&self) -> f64 { self.tolerance }
+
+    /// The maximum number of iterations of the per sample solve.
+    pub fn max_n_iterations(&self) -> u64 { self.max_n_iterations }
+
+    /// The seed of the initial basis.
+    pub fn random_seed(&self) -> u64 { self.random_seed }
+
+    fn build(
+        n_features: usize,
+        rank: usize,
+        lambda: f64,
+        ridge: f64,
+        tolerance: f64,
+        max_n_iterations: u64,
+        random_seed: u64,
+    ) -> Result<Self> {
+        if n_features == 0 {
+            return Err(Error::InvalidHyperParameter("`n_features` cannot be 0!".to_string()));
+        }
+        if rank == 0 {
+            return Err(Error::InvalidHyperParameter("`rank` cannot be 0!".to_string()));
+        }
+        if rank > n_features {
+            return Err(Error::TooManyComponents { requested: rank, max: n_features });
+        }
+        if !lambda.is_finite() || lambda <= 0.0 {
+            return Err(Error::InvalidHyperParameter("`lambda` must be greater than 0!".to_string()));
+        }
+        if !ridge.is_finite() || ridge <= 0.0 {
+            return Err(Error::InvalidHyperParameter("`ridge` must be greater than 0!".to_string()));
+        }
+        if tolerance.is_nan() || tolerance <= 0. {
+            return Err(Error::InvalidHyperParameter("`tolerance` must be greater than 0!".to_string()));
+        }
+        if max_n_iterations == 0 {
+            return Err(Error::InvalidHyperParameter("`max_n_iterations` cannot be 0!".to_string()));
+        }
+        Ok(OnlineRPCAHyperParams {
+            n_features,
+            rank,
+            lambda,
+            ridge,
+            tolerance,
+            max_n_iterations,
+            random_seed,
+        })
+    }
+}
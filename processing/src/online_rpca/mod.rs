@@ -0,0 +1,5 @@
+mod algorithm;
+mod hyperparameters;
+
+pub use self::algorithm::*;
+pub use self::hyperparameters::*;
@@ -0,0 +1,1027 @@
+#![allow(non_snake_case, non_camel_case_types)]
+use ndarray::{ArrayBase, Array2, Array1, Axis, Data, Ix2, s};
+use ndarray_linalg::SVD;
+use ndarray_rand::rand::{SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+use sprs::CsMatBase;
+use std::ops::Deref;
+use crate::error::{check_input, Error, Result};
+use crate::float::{Element, Float};
+use crate::linalg::{conjugate_transpose, eigh_svd, randomized_svd, CenteredSparse, LinearOperator};
+use crate::pca::hyperparameters::{Components, PCAHyperParams, SvdSolver};
+use std::f64::consts::PI;
+
+/// Principal component analysis of `f32`, `f64`, `c32` or `c64` data, computed
+/// in the precision of the input.
+///
+/// Complex data is decomposed with conjugate transposes, the principal axes are
+/// the eigenvectors of the Hermitian covariance and the variances are real.
+#[derive(Serialize, Deserialize)]
+// `Element` already requires the elements and their real type to be serializable
+#[serde(bound = "")]
+pub struct PCA<A: Element = f64> {
+    /// principal axes in feature space, shape (n_components, n_features)
+    components: Array2<A>,
+    /// projection of the training samples onto the principal axes, whitened if requested
+    scores: Array2<A>,
+    mean : Array1<A>,
+    explained_variance: Array1<A::Real>,
+    explained_variance_ratio: Array1<A::Real>,
+    ratio_cumsum: Array1<A::Real>,
+    singular_values: Array1<A::Real>,
+    total_variance: A::Real,
+    n_samples: usize,
+    n_features: usize,
+    hyperparameters: PCAHyperParams,
+}
+
+impl<A, R> PCA<A>
+where
+    A: Element<Real = R>,
+    R: Float,
+{
+
+    /// Fit the model on `x`, of shape (n_samples, n_features), keeping the
+    /// principal components selected by the hyperparameters.
+    ///
+    /// **Panics** if `try_fit` returns an error.
+    pub fn fit (
+        hyperparameters: PCAHyperParams,
+        x : &ArrayBase<impl Data<Elem = A>, Ix2>,
+    ) -> Self {
+        match PCA::try_fit(hyperparameters, x) {
+            Ok(model) => model,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Fit the model on `x`, of shape (n_samples, n_features), keeping the
+    /// principal components selected by the hyperparameters.
+    ///
+    /// Fails if `x` has fewer than two samples or non-finite values, if
+    /// `n_components` is not valid for the shape of `x` or if the SVD fails.
+    pub fn try_fit (
+        hyperparameters: PCAHyperParams,
+        x : &ArrayBase<impl Data<Elem = A>, Ix2>,
+    ) -> Result<Self> {
+
+        check_input(x, 2)?;
+        let (_n, _m) = x.dim();
+        let n_components = hyperparameters.n_components;
+        n_components.validate(_n, _m)?;
+
+        //calculate the array of columnar means
+        let mean = if hyperparameters.center {
+            x.mean_axis(Axis(0)).ok_or(Error::EmptyInput)?
+        } else {
+            Array1::zeros(_m)
+        };
+
+        // subtract means from X
+        let b = x - &mean;
+
+        // compute SVD
+        let (u, sigma, v) = match hyperparameters.solver.resolve(_n, _m) {
+            SvdSolver::Full | SvdSolver::Auto => {
+                let (u, sigma, v) = b.svd(true, true)?;
+                (
+                    u.expect("U was requested from the SVD"),
+                    sigma,
+                    v.expect("V^T was requested from the SVD"),
+                )
+            }
+            SvdSolver::Randomized { n_oversamples, n_power_iterations } => {
+                // validation guarantees a count, the randomized SVD only returns that many components
+                let k = match n_components {
+                    Components::Count(k) => k,
+                    _ => return Err(Error::InvalidHyperParameter(
+                        "`SvdSolver::Randomized` requires `Components::Count`!".to_string(),
+                    )),
+                };
+                let mut rng = StdRng::seed_from_u64(hyperparameters.random_seed);
+                randomized_svd(&b, k, n_oversamples, n_power_iterations, &mut rng)?
+            }
+            SvdSolver::Covariance => eigh_svd(&b)?,
+        };
+
+        // computed from the data so that truncated solvers report the same total
+        let total_var = b.map(|x| x.square()).sum() / A::real(_n - 1);
+        PCA::from_svd(hyperparameters, u, sigma, v, mean, total_var)
+    }
+
+    /// Fit the model on the sparse `x`, in CSR or CSC storage, of shape
+    /// (n_samples, n_features), through the randomized SVD.
+    ///
+    /// The centered data is never formed, the SVD only multiplies x - 1 mean^T
+    /// with dense blocks of `n_components + n_oversamples` vectors, so the cost
+    /// scales with the number of stored entries of `x`.
+    ///
+    /// **Panics** if `try_fit_sparse` returns an error.
+    pub fn fit_sparse<IpS, IS, DS>(
+        hyperparameters: PCAHyperParams,
+        x : &CsMatBase<A, usize, IpS, IS, DS>,
+    ) -> Self
+    where
+        IpS: Deref<Target = [usize]>,
+        IS: Deref<Target = [usize]>,
+        DS: Deref<Target = [A]>,
+    {
+        match PCA::try_fit_sparse(hyperparameters, x) {
+            Ok(model) => model,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Fit the model on the sparse `x`, in CSR or CSC storage, of shape
+    /// (n_samples, n_features), through the randomized SVD.
+    ///
+    /// Fails if the solver is not `SvdSolver::Randomized`, if `x` has fewer than
+    /// two samples or non-finite values, if `n_components` is not valid for the
+    /// shape of `x` or if the SVD fails.
+    pub fn try_fit_sparse<IpS, IS, DS>(
+        hyperparameters: PCAHyperParams,
+        x : &CsMatBase<A, usize, IpS, IS, DS>,
+    ) -> Result<Self>
+    where
+        IpS: Deref<Target = [usize]>,
+        IS: Deref<Target = [usize]>,
+        DS: Deref<Target = [A]>,
+    {
+        let (n_oversamples, n_power_iterations) = match hyperparameters.solver {
+            SvdSolver::Randomized { n_oversamples, n_power_iterations } => (n_oversamples, n_power_iterations),
+            _ => return Err(Error::InvalidHyperParameter(
+                "`PCA::fit_sparse` requires `SvdSolver::Randomized`!".to_string(),
+            )),
+        };
+        let (_n, _m) = (x.rows(), x.cols());
+        if _n < 2 || _m == 0 {
+            return Err(Error::EmptyInput);
+        }
+        if x.data().iter().any(|v| !(v.re().is_finite() && v.im().is_finite())) {
+            return Err(Error::NonFiniteValue);
+        }
+        let n_components = hyperparameters.n_components;
+        n_components.validate(_n, _m)?;
+        // validation guarantees a count for the randomized solver
+        let k = match n_components {
+            Components::Count(k) => k,
+            _ => return Err(Error::InvalidHyperParameter(
+                "`SvdSolver::Randomized` requires `Components::Count`!".to_string(),
+            )),
+        };
+
+        let mut mean = Array1::zeros(_m);
+        if hyperparameters.center {
+            for (&v, (_, j)) in x.iter() {
+                mean[j] += v;
+            }
+            mean.mapv_inplace(|v: A| v.div_real(A::real(_n)));
+        }
+
+        let b = CenteredSparse { x, mean: &mean };
+        let mut rng = StdRng::seed_from_u64(hyperparameters.random_seed);
+        let (u, sigma, v) = randomized_svd(&b, k, n_oversamples, n_power_iterations, &mut rng)?;
+
+        // ||x - 1 mean^T||_F^2 = ||x||_F^2 - n ||mean||^2
+        let squares = x.data().iter().map(|v| v.square()).fold(R::zero(), |a, b| a + b);
+        let shift = mean.iter().map(|v| v.square()).fold(R::zero(), |a, b| a + b) * R::real(_n);
+        let total_var = (squares - shift).max(R::zero()) / R::real(_n - 1);
+        PCA::from_svd(hyperparameters, u, sigma, v, mean, total_var)
+    }
+
+    /// Keep the components selected by the hyperparameters from the SVD u sigma v
+    /// of the centered training data.
+    fn from_svd(
+        hyperparameters: PCAHyperParams,
+        u: Array2<A>,
+        sigma: Array1<R>,
+        v: Array2<A>,
+        mean: Array1<A>,
+        total_var: R,
+    ) -> Result<Self> {
+        let n_components = hyperparameters.n_components;
+        let (_n, _m) = (u.nrows(), v.ncols());
+        let temp = A::real(_n - 1);
+        let explained_variance = sigma.map(|x| x.powi(2)).map(|&x|  x / temp );
+        let explained_variance_ratio = explained_variance.map(|&x| x / total_var);
+
+        let mut ratio_cumsum = explained_variance_ratio.clone();
+        ratio_cumsum.accumulate_axis_inplace(Axis(0), |&prev, curr| *curr += prev);
+
+        // find the number of components to keep
+        let k = match n_components {
+            Components::Count(k) => k,
+            Components::VarianceRatio(ratio) => {
+                // smallest number of components whose cumulative ratio exceeds `ratio`
+                ratio_cumsum.iter()
+                    .position(|&c| c > R::real(ratio))
+                    .map_or(sigma.len(), |i| i + 1)
+            }
+            Components::All => sigma.len(),
+            Components::Mle => infer_dimension(&explained_variance.mapv(|x| x.to_f64().expect("a float converts to f64")), _n),
+            Components::SingularValueThreshold(threshold) => {
+                let k = sigma.iter().filter(|&&s| s > R::real(threshold)).count();
+                if k == 0 {
+                    return Err(Error::InvalidHyperParameter(
+                        format!("no singular value is greater than {}!", threshold),
+                    ));
+                }
+                k
+            }
+        };
+
+        // v holds V^H, so the principal axes are its leading rows
+        let components = v.slice(s![..k, ..]).to_owned();
+        let singular_values = sigma.slice(s![..k]).to_owned();
+        // whitening divides u * sigma by sigma / sqrt(n - 1)
+        let scores = if hyperparameters.whiten {
+            u.slice(s![.., ..k]).mapv(|x| x.mul_real(temp.sqrt()))
+        } else {
+            u.slice(s![.., ..k]).to_owned() * &singular_values.mapv(A::from_real)
+        };
+
+        Ok(Self {
+            components,
+            scores,
+            mean,
+            explained_variance: explained_variance.slice(s![..k]).to_owned(),
+            explained_variance_ratio: explained_variance_ratio.slice(s![..k]).to_owned(),
+            ratio_cumsum: ratio_cumsum.slice(s![..k]).to_owned(),
+            singular_values,
+            total_variance: total_var,
+            n_samples: _n,
+            n_features: _m,
+            hyperparameters,
+        })
+    }
+
+    /// Project `x` onto the retained principal axes, after centering it with the
+    /// mean seen during `fit`. Returns an array of shape (n_samples, n_components).
+    ///
+    /// When whitening is enabled each projection is divided by the square root
+    /// of the explained variance of its component.
+    ///
+    /// **Panics** if `try_transform` returns an error.
+    pub fn transform (
+        &self,
+        x : &ArrayBase<impl Data<Elem = A>, Ix2>,
+    ) -> Array2<A> {
+        match self.try_transform(x) {
+            Ok(z) => z,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Project `x` onto the retained principal axes, after centering it with the
+    /// mean seen during `fit`. Returns an array of shape (n_samples, n_components).
+    ///
+    /// Fails if `x` does not have the number of features seen during `fit`.
+    pub fn try_transform (
+        &self,
+        x : &ArrayBase<impl Data<Elem = A>, Ix2>,
+    ) -> Result<Array2<A>> {
+        if x.ncols() != self.n_features {
+            return Err(Error::FeatureMismatch { expected: self.n_features, found: x.ncols() });
+        }
+        let centered = x - &self.mean;
+        let z = centered.dot(&conjugate_transpose(&self.components));
+        if self.hyperparameters.whiten {
+            Ok(z / &self.explained_variance.map(|x| A::from_real(x.sqrt())))
+        } else {
+            Ok(z)
+        }
+    }
+
+    /// Project the sparse `x`, in CSR or CSC storage, onto the retained principal
+    /// axes, centering it implicitly with the mean seen during `fit`. Returns a
+    /// dense array of shape (n_samples, n_components), whitened if requested.
+    ///
+    /// **Panics** if `try_transform_sparse` returns an error.
+    pub fn transform_sparse<IpS, IS, DS>(
+        &self,
+        x : &CsMatBase<A, usize, IpS, IS, DS>,
+    ) -> Array2<A>
+    where
+        IpS: Deref<Target = [usize]>,
+        IS: Deref<Target = [usize]>,
+        DS: Deref<Target = [A]>,
+    {
+        match self.try_transform_sparse(x) {
+            Ok(z) => z,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Project the sparse `x`, in CSR or CSC storage, onto the retained principal
+    /// axes, centering it implicitly with the mean seen during `fit`.
+    ///
+    /// Fails if `x` does not have the number of features seen during `fit`.
+    pub fn try_transform_sparse<IpS, IS, DS>(
+        &self,
+        x : &CsMatBase<A, usize, IpS, IS, DS>,
+    ) -> Result<Array2<A>>
+    where
+        IpS: Deref<Target = [usize]>,
+        IS: Deref<Target = [usize]>,
+        DS: Deref<Target = [A]>,
+    {
+        if x.cols() != self.n_features {
+            return Err(Error::FeatureMismatch { expected: self.n_features, found: x.cols() });
+        }
+        let centered = CenteredSparse { x, mean: &self.mean };
+        let z = centered.dot(&conjugate_transpose(&self.components));
+        if self.hyperparameters.whiten {
+            Ok(z / &self.explained_variance.map(|x| A::from_real(x.sqrt())))
+        } else {
+            Ok(z)
+        }
+    }
+
+    /// Map scores produced by `transform` back to the original feature space.
+    ///
+    /// **Panics** if `try_inverse_transform` returns an error.
+    pub fn inverse_transform (
+        &self,
+        z : &ArrayBase<impl Data<Elem = A>, Ix2>,
+    ) -> Array2<A> {
+        match self.try_inverse_transform(z) {
+            Ok(x) => x,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Map scores produced by `transform` back to the original feature space.
+    ///
+    /// Fails if `z` does not have one column per retained component.
+    pub fn try_inverse_transform (
+        &self,
+        z : &ArrayBase<impl Data<Elem = A>, Ix2>,
+    ) -> Result<Array2<A>> {
+        let n_components = self.components.nrows();
+        if z.ncols() != n_components {
+            return Err(Error::ComponentMismatch { expected: n_components, found: z.ncols() });
+        }
+        if self.hyperparameters.whiten {
+            let z = z * &self.explained_variance.map(|x| A::from_real(x.sqrt()));
+            Ok(z.dot(&self.components) + &self.mean)
+        } else {
+            Ok(z.dot(&self.components) + &self.mean)
+        }
+    }
+
+    pub fn mean(&self) -> &Array1<A> {
+        &self.mean
+    }
+
+    /// The principal axes as rows, shape (n_components, n_features). For complex
+    /// data these are the rows of V^H, the conjugates of the eigenvectors.
+    pub fn components(&self) -> &Array2<A> {
+        &self.components
+    }
+
+    /// The training samples projected onto the principal axes, shape (n_samples, n_components).
+    /// Equal to `transform` of the training data, whitening included.
+    pub fn scores(&self) -> &Array2<A> {
+        &self.scores
+    }
+
+    /// Variance of the training data along each retained principal axis.
+    pub fn explained_variance(&self) -> &Array1<R> {
+        &self.explained_variance
+    }
+
+    /// Fraction of the total variance explained by each retained principal axis.
+    pub fn explained_variance_ratio(&self) -> &Array1<R> {
+        &self.explained_variance_ratio
+    }
+
+    /// Cumulative sum of `explained_variance_ratio`.
+    pub fn explained_variance_ratio_cumsum(&self) -> &Array1<R> {
+        &self.ratio_cumsum
+    }
+
+    /// Singular values of the centered training data for each retained component.
+    pub fn singular_values(&self) -> &Array1<R> {
+        &self.singular_values
+    }
+
+    /// Total variance of the training data, summed over every feature.
+    pub fn total_variance(&self) -> R {
+        self.total_variance
+    }
+
+    /// The number of samples seen during `fit`.
+    pub fn n_samples(&self) -> usize {
+        self.n_samples
+    }
+
+    /// The number of features seen during `fit`.
+    pub fn n_features(&self) -> usize {
+        self.n_features
+    }
+
+    /// The hyperparameters used during `fit`.
+    pub fn hyperparameters(&self) -> &PCAHyperParams {
+        &self.hyperparameters
+    }
+}
+
+/// Minka's log-likelihood of the data having the given `rank`, from
+/// "Automatic choice of dimensionality for PCA", NIPS 2000.
+fn assess_dimension(spectrum : &Array1<f64>, rank : usize, n_samples : usize) -> f64 {
+    let n_features = spectrum.len();
+    let n = n_samples as f64;
+    let r = rank as f64;
+    if spectrum[rank - 1] < f64::EPSILON {
+        return f64::NEG_INFINITY;
+    }
+
+    let mut pu = -r * 2f64.ln();
+    for i in 1..=rank {
+        let d = (n_features - i + 1) as f64 / 2.0;
+        pu += ln_gamma(d) - PI.ln() * d;
+    }
+
+    let pl = -spectrum.slice(s![..rank]).map(|x| x.ln()).sum() * n / 2.0;
+
+    let v = (spectrum.slice(s![rank..]).sum() / (n_features - rank) as f64).max(f64::EPSILON);
+    let pv = -v.ln() * n * (n_features - rank) as f64 / 2.0;
+
+    let m = n_features as f64 * r - r * (r + 1.0) / 2.0;
+    let pp = (2.0 * PI).ln() * (m + r) / 2.0;
+
+    let mut pa = 0.0;
+    let corrected = |j : usize| if j < rank { spectrum[j] } else { v };
+    for i in 0..rank {
+        for j in (i + 1)..n_features {
+            pa += ((spectrum[i] - spectrum[j]) * (1.0 / corrected(j) - 1.0 / corrected(i))).ln() + n.ln();
+        }
+    }
+
+    pu + pl + pv + pp - pa / 2.0 - r * n.ln() / 2.0
+}
+
+/// The rank maximising `assess_dimension` over the explained variance spectrum.
+fn infer_dimension(spectrum : &Array1<f64>, n_samples : usize) -> usize {
+    let mut best = 1;
+    let mut best_ll = f64::NEG_INFINITY;
+    for rank in 1..spectrum.len() {
+        let ll = assess_dimension(spectrum, rank, n_samples);
+        if ll > best_ll {
+            best = rank;
+            best_ll = ll;
+        }
+    }
+    best
+}
+
+/// Natural logarithm of the gamma function, using the Lanczos approximation.
+fn ln_gamma(x : f64) -> f64 {
+    const G: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // reflection formula
+        return (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let mut a = G[0];
+    let t = x + 7.5;
+    for (i, g) in G.iter().enumerate().skip(1) {
+        a += g / (x + i as f64);
+    }
+    0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + a.ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{Array, Array2};
+    use ndarray_linalg::{c32, c64, Eigh, UPLO};
+    use ndarray_rand::RandomExt;
+    use ndarray_rand::rand::Rng;
+    use ndarray_rand::rand_distr::Uniform;
+    use sprs::{CsMat, TriMat};
+
+    #[test]
+    fn test_PCA() {
+
+        // Let's setup a synthetic set of observations, composed of two clusters with known means
+        let X: Array2<f64> = Array::random((100, 100), Uniform::new(-100., 100.));
+
+        let _model = PCA::fit(PCAHyperParams::new(Components::All).build(), &X);
+    }
+
+    #[test]
+    fn test_PCA_transform_round_trip() {
+        // keeping every component the projection must be lossless
+        let X: Array2<f64> = Array::random((50, 10), Uniform::new(-10., 10.));
+
+        let model = PCA::fit(PCAHyperParams::new(Components::Count(10)).build(), &X);
+        let Z = model.transform(&X);
+        assert_eq!(Z.dim(), (50, 10));
+
+        let X_hat = model.inverse_transform(&Z);
+        let err = (&X_hat - &X).map(|x| x.abs()).fold(0.0_f64, |a, &b| a.max(b));
+        assert!(err < 1e-8);
+    }
+
+    #[test]
+    fn test_PCA_components_are_principal_axes() {
+        let X: Array2<f64> = Array::random((60, 8), Uniform::new(-10., 10.));
+
+        let model = PCA::fit(PCAHyperParams::new(Components::Count(3)).build(), &X);
+        let V = model.components();
+        assert_eq!(V.dim(), (3, 8));
+        assert_eq!(model.scores().dim(), (60, 3));
+
+        // the axes are orthonormal
+        let gram = V.dot(&V.t());
+        for i in 0..3 {
+            for j in 0..3 {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((gram[[i, j]] - expected).abs() < 1e-10);
+            }
+        }
+
+        // and match, up to sign, the leading eigenvectors of the covariance matrix
+        let centered = &X - model.mean();
+        let cov = centered.t().dot(&centered) / 59.0;
+        let (_, eigvecs) = cov.eigh(UPLO::Upper).unwrap();
+        for k in 0..3 {
+            // eigh sorts eigenvalues in ascending order
+            let reference = eigvecs.column(7 - k);
+            let cosine = V.row(k).dot(&reference);
+            assert!((cosine.abs() - 1.0).abs() < 1e-8);
+        }
+
+        // training scores are the projection of the training data
+        let Z = model.transform(&X);
+        let err = (&Z - model.scores()).map(|x| x.abs()).fold(0.0_f64, |a, &b| a.max(b));
+        assert!(err < 1e-8);
+    }
+
+    #[test]
+    fn test_PCA_explained_variance() {
+        let X: Array2<f64> = Array::random((40, 6), Uniform::new(-5., 5.));
+
+        let model = PCA::fit(PCAHyperParams::new(Components::Count(4)).build(), &X);
+        assert_eq!(model.n_samples(), 40);
+        assert_eq!(model.n_features(), 6);
+        assert_eq!(model.explained_variance().len(), 4);
+        assert_eq!(model.explained_variance_ratio().len(), 4);
+        assert_eq!(model.explained_variance_ratio_cumsum().len(), 4);
+        assert_eq!(model.singular_values().len(), 4);
+
+        // the total variance is the sum of the per-feature sample variances
+        let total: f64 = X.var_axis(Axis(0), 1.0).sum();
+        assert!((model.total_variance() - total).abs() < 1e-8);
+
+        // the retained components explain less than the whole variance
+        let cumsum = model.explained_variance_ratio_cumsum();
+        assert!(cumsum[3] < 1.0);
+        assert!((cumsum[3] - model.explained_variance_ratio().sum()).abs() < 1e-12);
+        for k in 0..4 {
+            let sigma = model.singular_values()[k];
+            assert!((sigma.powi(2) / 39.0 - model.explained_variance()[k]).abs() < 1e-8);
+        }
+
+        // keeping every component explains all of it
+        let full = PCA::fit(PCAHyperParams::new(Components::All).build(), &X);
+        assert!((full.explained_variance_ratio().sum() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_PCA_component_selection() {
+        let X: Array2<f64> = Array::random((30, 5), Uniform::new(-5., 5.));
+
+        assert_eq!(PCA::fit(PCAHyperParams::new(Components::Count(1)).build(), &X).components().nrows(), 1);
+        assert_eq!(PCA::fit(PCAHyperParams::new(Components::All).build(), &X).components().nrows(), 5);
+        assert_eq!(PCA::fit(PCAHyperParams::new(Components::VarianceRatio(1.0)).build(), &X).components().nrows(), 5);
+
+        let model = PCA::fit(PCAHyperParams::new(Components::VarianceRatio(0.5)).build(), &X);
+        let k = model.components().nrows();
+        assert!(model.explained_variance_ratio_cumsum()[k - 1] > 0.5);
+        if k > 1 {
+            assert!(model.explained_variance_ratio_cumsum()[k - 2] <= 0.5);
+        }
+
+        let full = PCA::fit(PCAHyperParams::new(Components::All).build(), &X);
+        let threshold = full.singular_values()[2];
+        assert_eq!(PCA::fit(PCAHyperParams::new(Components::SingularValueThreshold(threshold)).build(), &X).components().nrows(), 2);
+    }
+
+    #[test]
+    fn test_PCA_mle_finds_low_rank_structure() {
+        // rank 2 signal embedded in 10 features with a little isotropic noise
+        let W: Array2<f64> = Array::random((2, 10), Uniform::new(-1., 1.));
+        let H: Array2<f64> = Array::random((200, 2), Uniform::new(-10., 10.));
+        let noise: Array2<f64> = Array::random((200, 10), Uniform::new(-0.01, 0.01));
+        let X = H.dot(&W) + noise;
+
+        let model = PCA::fit(PCAHyperParams::new(Components::Mle).build(), &X);
+        assert_eq!(model.components().nrows(), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_PCA_too_many_components() {
+        let X: Array2<f64> = Array::random((10, 4), Uniform::new(-5., 5.));
+        PCA::fit(PCAHyperParams::new(Components::Count(5)).build(), &X);
+    }
+
+    #[test]
+    fn test_ln_gamma() {
+        assert!(ln_gamma(1.0).abs() < 1e-12);
+        assert!((ln_gamma(5.0) - 24f64.ln()).abs() < 1e-10);
+        assert!((ln_gamma(0.5) - PI.sqrt().ln()).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_PCA_hyperparameters() {
+        let X: Array2<f64> = Array::random((30, 5), Uniform::new(0., 5.));
+
+        let hyperparams = PCAHyperParams::new(Components::Count(2))
+            .center(false)
+            .random_seed(7)
+            .build();
+        assert_eq!(hyperparams.n_components(), Components::Count(2));
+        assert!(!hyperparams.whiten());
+        assert!(!hyperparams.center());
+        assert_eq!(hyperparams.solver(), SvdSolver::Full);
+        assert_eq!(hyperparams.random_seed(), 7);
+
+        let model = PCA::fit(hyperparams.clone(), &X);
+        assert_eq!(model.hyperparameters(), &hyperparams);
+        assert!(model.mean().iter().all(|&x| x == 0.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_PCAHyperParams_invalid_ratio() {
+        PCAHyperParams::new(Components::VarianceRatio(1.5)).build();
+    }
+
+    #[test]
+    fn test_PCA_try_fit_errors() {
+        let hyperparams = PCAHyperParams::new(Components::Count(2)).build();
+
+        let X: Array2<f64> = Array2::zeros((1, 4));
+        assert!(matches!(PCA::try_fit(hyperparams.clone(), &X), Err(Error::EmptyInput)));
+
+        let mut X: Array2<f64> = Array::random((10, 4), Uniform::new(-5., 5.));
+        X[[3, 1]] = f64::INFINITY;
+        assert!(matches!(PCA::try_fit(hyperparams, &X), Err(Error::NonFiniteValue)));
+
+        let X: Array2<f64> = Array::random((10, 4), Uniform::new(-5., 5.));
+        let hyperparams = PCAHyperParams::new(Components::Count(5)).build();
+        assert!(matches!(
+            PCA::try_fit(hyperparams, &X),
+            Err(Error::TooManyComponents { requested: 5, max: 4 })
+        ));
+
+        assert!(matches!(
+            PCAHyperParams::new(Components::Count(0)).try_build(),
+            Err(Error::InvalidHyperParameter(_))
+        ));
+
+        // projections with the wrong number of features or components
+        let model = PCA::fit(PCAHyperParams::new(Components::Count(2)).build(), &X);
+        let wrong: Array2<f64> = Array2::zeros((3, 5));
+        assert!(matches!(
+            model.try_transform(&wrong),
+            Err(Error::FeatureMismatch { expected: 4, found: 5 })
+        ));
+        assert!(matches!(
+            model.try_inverse_transform(&wrong),
+            Err(Error::ComponentMismatch { expected: 2, found: 5 })
+        ));
+        assert!(model.try_inverse_transform(&model.try_transform(&X).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_PCA_randomized_solver() {
+        // a wide matrix with a quickly decaying spectrum
+        let W: Array2<f64> = Array::random((5, 300), Uniform::new(-1., 1.));
+        let H: Array2<f64> = Array::random((80, 5), Uniform::new(-10., 10.));
+        let noise: Array2<f64> = Array::random((80, 300), Uniform::new(-0.1, 0.1));
+        let X = H.dot(&W) + noise;
+
+        let solver = SvdSolver::Randomized { n_oversamples: 10, n_power_iterations: 4 };
+        let hyperparams = PCAHyperParams::new(Components::Count(5))
+            .solver(solver)
+            .random_seed(3)
+            .build();
+        let randomized = PCA::fit(hyperparams.clone(), &X);
+        let full = PCA::fit(PCAHyperParams::new(Components::Count(5)).build(), &X);
+
+        assert_eq!(randomized.components().dim(), (5, 300));
+        assert!((randomized.total_variance() - full.total_variance()).abs() < 1e-8);
+        for k in 0..5 {
+            let cosine = randomized.components().row(k).dot(&full.components().row(k));
+            assert!((cosine.abs() - 1.0).abs() < 1e-6);
+            let rel = (randomized.singular_values()[k] - full.singular_values()[k]) / full.singular_values()[k];
+            assert!(rel.abs() < 1e-8);
+        }
+
+        // the seed makes the fit reproducible
+        let again = PCA::fit(hyperparams, &X);
+        assert_eq!(again.components(), randomized.components());
+
+        assert!(matches!(
+            PCAHyperParams::new(Components::VarianceRatio(0.9)).solver(solver).try_build(),
+            Err(Error::InvalidHyperParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_PCA_covariance_solver() {
+        // a tall matrix goes through the feature covariance, a wide one through the Gram matrix
+        for &(n, m) in &[(400, 8), (6, 120)] {
+            let X: Array2<f64> = Array::random((n, m), Uniform::new(-10., 10.));
+            let k = n.min(m) - 1;
+            assert_eq!(SvdSolver::Auto.resolve(n, m), SvdSolver::Covariance);
+
+            let model = PCA::fit(
+                PCAHyperParams::new(Components::Count(k)).solver(SvdSolver::Covariance).build(),
+                &X,
+            );
+            let full = PCA::fit(
+                PCAHyperParams::new(Components::Count(k)).solver(SvdSolver::Full).build(),
+                &X,
+            );
+            let V = model.components();
+            let sigma = model.singular_values();
+
+            // orthonormal axes
+            let gram = V.dot(&V.t()) - Array2::<f64>::eye(k);
+            assert!(gram.iter().all(|x| x.abs() < 1e-10));
+
+            // which are right singular vectors of the centered data, with the same spectrum as the SVD
+            let centered = &X - model.mean();
+            let residual = centered.t().dot(&centered.dot(&V.t())) - &V.t() * &sigma.map(|x| x * x);
+            assert!(residual.iter().all(|x| x.abs() < 1e-10 * sigma[0] * sigma[0]));
+            for i in 0..k {
+                assert!(((sigma[i] - full.singular_values()[i]) / full.singular_values()[i]).abs() < 1e-6);
+            }
+            assert!((model.total_variance() - full.total_variance()).abs() < 1e-8 * full.total_variance());
+
+            // training scores are the projection of the training data
+            let projected = model.transform(&X);
+            assert!((model.scores() - &projected).iter().all(|x| x.abs() < 1e-6));
+        }
+
+        assert_eq!(SvdSolver::Auto.resolve(100, 100), SvdSolver::Full);
+        assert_eq!(SvdSolver::Auto.resolve(50_000, 2000), SvdSolver::Full);
+        assert_eq!(SvdSolver::Full.resolve(400, 8), SvdSolver::Full);
+    }
+
+    #[test]
+    fn test_PCA_whiten() {
+        // correlated features with very different variances
+        let mut rng = StdRng::seed_from_u64(17);
+        let H: Array2<f64> = Array::random_using((500, 4), Uniform::new(-1., 1.), &mut rng);
+        let W: Array2<f64> = Array::random_using((4, 6), Uniform::new(-5., 5.), &mut rng);
+        let X = H.dot(&W) + 7.0;
+
+        for &solver in &[SvdSolver::Full, SvdSolver::Covariance] {
+            let model = PCA::fit(PCAHyperParams::new(Components::Count(3)).whiten(true).solver(solver).build(), &X);
+            let Z = model.transform(&X);
+            assert!((model.scores() - &Z).iter().all(|x| x.abs() < 1e-8));
+
+            // the whitened training scores have an identity covariance
+            let centered = &Z - &Z.mean_axis(Axis(0)).unwrap();
+            let covariance = centered.t().dot(&centered) / 499.0;
+            assert!((covariance - Array2::<f64>::eye(3)).iter().all(|x| x.abs() < 1e-8));
+
+            // and inverse_transform undoes the scaling
+            let unwhitened = PCA::fit(PCAHyperParams::new(Components::Count(3)).solver(solver).build(), &X);
+            let reconstruction = model.inverse_transform(&Z);
+            let expected = unwhitened.inverse_transform(&unwhitened.transform(&X));
+            assert!((reconstruction - expected).iter().all(|x| x.abs() < 1e-8));
+        }
+
+        // keeping every component the round trip is lossless
+        let model = PCA::fit(PCAHyperParams::new(Components::All).whiten(true).build(), &X.slice(s![.., ..4]));
+        let reconstruction = model.inverse_transform(&model.transform(&X.slice(s![.., ..4])));
+        assert!((reconstruction - X.slice(s![.., ..4])).iter().all(|x| x.abs() < 1e-8));
+    }
+
+    #[test]
+    fn test_PCA_f32() {
+        let X: Array2<f32> = Array::random((200, 6), Uniform::new(-10f32, 10.));
+        let X64 = X.mapv(f64::from);
+
+        for &solver in &[SvdSolver::Full, SvdSolver::Covariance, SvdSolver::Randomized { n_oversamples: 6, n_power_iterations: 4 }] {
+            let hyperparams = PCAHyperParams::new(Components::Count(6)).solver(solver).build();
+            let model: PCA<f32> = PCA::fit(hyperparams.clone(), &X);
+            let reference = PCA::fit(hyperparams, &X64);
+
+            // the f32 fit agrees with the f64 one to single precision
+            for k in 0..6 {
+                let rel = (f64::from(model.explained_variance()[k]) - reference.explained_variance()[k]) / reference.explained_variance()[k];
+                assert!(rel.abs() < 1e-4);
+            }
+
+            // and round-trips f32 data without casts
+            let reconstruction: Array2<f32> = model.inverse_transform(&model.transform(&X));
+            assert!((reconstruction - &X).iter().all(|x| x.abs() < 1e-3));
+        }
+    }
+
+    #[test]
+    fn test_PCA_rank_deficient_f32() {
+        // rank 3 data, tall and wide, so that most singular values vanish
+        let mut rng = StdRng::seed_from_u64(23);
+        for &(n, m) in &[(400, 8), (6, 120)] {
+            let H: Array2<f32> = Array::random_using((n, 3), Uniform::new(-10f32, 10.), &mut rng);
+            let W: Array2<f32> = Array::random_using((3, m), Uniform::new(-1f32, 1.), &mut rng);
+            let X = H.dot(&W);
+            let k = n.min(m);
+
+            // every solver returns orthonormal axes, not zero rows for the vanishing components
+            let default = PCAHyperParams::new(Components::All).build();
+            assert_eq!(default.solver(), SvdSolver::Full);
+            for hyperparams in [default, PCAHyperParams::new(Components::All).solver(SvdSolver::Covariance).build()] {
+                let model: PCA<f32> = PCA::fit(hyperparams, &X);
+                let V = model.components();
+                assert_eq!(V.dim(), (k, m));
+                let gram = V.dot(&V.t()) - Array2::<f32>::eye(k);
+                assert!(gram.iter().all(|x| x.abs() < 1e-4));
+            }
+        }
+    }
+
+    fn complex_data(n: usize, m: usize) -> Array2<c64> {
+        // a complex rank 3 signal plus a little complex noise
+        let re: Array2<f64> = Array::random((n, 3), Uniform::new(-10., 10.));
+        let im: Array2<f64> = Array::random((n, 3), Uniform::new(-10., 10.));
+        let H = Array2::from_shape_fn((n, 3), |(i, j)| c64::new(re[[i, j]], im[[i, j]]));
+        let re: Array2<f64> = Array::random((3, m), Uniform::new(-1., 1.));
+        let im: Array2<f64> = Array::random((3, m), Uniform::new(-1., 1.));
+        let W = Array2::from_shape_fn((3, m), |(i, j)| c64::new(re[[i, j]], im[[i, j]]));
+        let noise: Array2<f64> = Array::random((n, 2 * m), Uniform::new(-0.1, 0.1));
+        H.dot(&W) + Array2::from_shape_fn((n, m), |(i, j)| c64::new(noise[[i, j]], noise[[i, m + j]]))
+    }
+
+    /// Bag-of-words counts of `n` documents drawn from 3 topics, each with its own
+    /// block of 20 words, plus one stray word per document.
+    fn sparse_counts(n: usize) -> CsMat<f64> {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut triplets = TriMat::new((n, 60));
+        for i in 0..n {
+            let topic = i % 3;
+            for _ in 0..3 {
+                triplets.add_triplet(i, 20 * topic + rng.gen_range(0, 20), rng.gen_range(1, 4) as f64);
+            }
+            triplets.add_triplet(i, rng.gen_range(0, 60), 1.);
+        }
+        // duplicate entries are summed
+        triplets.to_csr()
+    }
+
+    #[test]
+    fn test_PCA_sparse() {
+        let csr = sparse_counts(300);
+        let csc = csr.to_csc();
+        let X = csr.to_dense();
+        let full = PCA::fit(PCAHyperParams::new(Components::Count(2)).build(), &X);
+        let randomized = SvdSolver::Randomized { n_oversamples: 10, n_power_iterations: 6 };
+
+        for sparse in &[&csr, &csc] {
+            for &whiten in &[false, true] {
+                let hyperparams = PCAHyperParams::new(Components::Count(2)).solver(randomized).whiten(whiten).build();
+                let model = PCA::fit_sparse(hyperparams.clone(), *sparse);
+                // the implicit centering reproduces the dense randomized SVD
+                let dense = PCA::fit(hyperparams, &X);
+                assert!((model.mean() - dense.mean()).iter().all(|x| x.abs() < 1e-12));
+                assert!((model.total_variance() - full.total_variance()).abs() < 1e-10 * full.total_variance());
+                assert!((model.components() - dense.components()).iter().all(|x| x.abs() < 1e-10));
+                for i in 0..2 {
+                    let (a, b) = (model.explained_variance()[i], dense.explained_variance()[i]);
+                    assert!((a - b).abs() < 1e-10 * b, "{} != {}", a, b);
+                    // and approaches the exact one
+                    let exact = full.explained_variance()[i];
+                    assert!((a - exact).abs() < 1e-3 * exact, "{} != {}", a, exact);
+                }
+
+                assert!((model.scores() - dense.scores()).iter().all(|x| x.abs() < 1e-8));
+
+                // the sparse projection matches the dense one
+                let projected = model.transform(&X);
+                assert!((model.transform_sparse(*sparse) - &projected).iter().all(|x| x.abs() < 1e-10));
+            }
+        }
+
+        // without centering the sparse fit decomposes x itself
+        let hyperparams = PCAHyperParams::new(Components::Count(2)).solver(randomized).center(false).build();
+        let uncentered = PCA::fit_sparse(hyperparams.clone(), &csr);
+        assert!(uncentered.mean().iter().all(|&x| x == 0.));
+        let dense = PCA::fit(hyperparams, &X);
+        assert!((uncentered.singular_values() - dense.singular_values()).iter().all(|x| x.abs() < 1e-10));
+    }
+
+    #[test]
+    fn test_PCA_sparse_errors() {
+        let csr = sparse_counts(30);
+        let randomized = SvdSolver::Randomized { n_oversamples: 5, n_power_iterations: 2 };
+        assert!(matches!(
+            PCA::try_fit_sparse(PCAHyperParams::new(Components::Count(2)).build(), &csr),
+            Err(Error::InvalidHyperParameter(_))
+        ));
+        assert!(matches!(
+            PCA::try_fit_sparse(PCAHyperParams::new(Components::Count(61)).solver(randomized).build(), &csr),
+            Err(Error::TooManyComponents { requested: 61, max: 30 })
+        ));
+
+        let one_sample: CsMat<f64> = CsMat::csr_from_dense(Array2::ones((1, 4)).view(), 0.);
+        assert!(matches!(
+            PCA::try_fit_sparse(PCAHyperParams::new(Components::Count(1)).solver(randomized).build(), &one_sample),
+            Err(Error::EmptyInput)
+        ));
+        let model = PCA::fit_sparse(PCAHyperParams::new(Components::Count(2)).solver(randomized).build(), &csr);
+        let narrow: CsMat<f64> = CsMat::csr_from_dense(Array2::ones((2, 59)).view(), 0.);
+        assert!(matches!(
+            model.try_transform_sparse(&narrow),
+            Err(Error::FeatureMismatch { expected: 60, found: 59 })
+        ));
+
+        let mut infinite = csr.clone();
+        infinite.data_mut()[3] = f64::INFINITY;
+        assert!(matches!(
+            PCA::try_fit_sparse(PCAHyperParams::new(Components::Count(2)).solver(randomized).build(), &infinite),
+            Err(Error::NonFiniteValue)
+        ));
+    }
+
+    #[test]
+    fn test_PCA_complex() {
+        let X = complex_data(120, 7);
+        let model: PCA<c64> = PCA::fit(PCAHyperParams::new(Components::All).build(), &X);
+        let V = model.components();
+
+        // the axes are orthonormal under the conjugate inner product
+        let gram = V.dot(&conjugate_transpose(V)) - Array2::<c64>::eye(7);
+        assert!(gram.iter().all(|x| x.norm() < 1e-10));
+
+        // the real explained variances are the eigenvalues of the Hermitian covariance
+        let centered = &X - model.mean();
+        let cov = conjugate_transpose(&centered).dot(&centered) / c64::new(119.0, 0.0);
+        let (w, _) = cov.eigh(UPLO::Upper).unwrap();
+        for k in 0..7 {
+            let rel = (model.explained_variance()[k] - w[6 - k]) / w[6 - k];
+            assert!(rel.abs() < 1e-6);
+        }
+        let total: f64 = centered.iter().map(|x| x.norm_sqr()).sum::<f64>() / 119.0;
+        assert!((model.total_variance() - total).abs() < 1e-8 * total);
+        assert!((model.explained_variance_ratio().sum() - 1.0).abs() < 1e-10);
+
+        // and their eigenvectors are the conjugated rows of V^H
+        let axes = conjugate_transpose(V);
+        let residual = cov.dot(&axes) - &axes * &model.explained_variance().mapv(c64::from);
+        assert!(residual.iter().all(|x| x.norm() < 1e-6 * w[6]));
+
+        // training scores are the projection of the training data, which is lossless
+        let Z = model.transform(&X);
+        assert!((model.scores() - &Z).iter().all(|x| x.norm() < 1e-8));
+        assert!((model.inverse_transform(&Z) - &X).iter().all(|x| x.norm() < 1e-8));
+
+        // the low-rank structure is found by every solver
+        let solvers = [
+            SvdSolver::Full,
+            SvdSolver::Covariance,
+            SvdSolver::Randomized { n_oversamples: 4, n_power_iterations: 4 },
+        ];
+        for &solver in &solvers {
+            let truncated: PCA<c64> = PCA::fit(PCAHyperParams::new(Components::Count(3)).solver(solver).build(), &X);
+            for k in 0..3 {
+                let rel = (truncated.explained_variance()[k] - model.explained_variance()[k]) / model.explained_variance()[k];
+                assert!(rel.abs() < 1e-6);
+            }
+            assert!(truncated.explained_variance_ratio_cumsum()[2] > 0.99);
+        }
+    }
+
+    #[test]
+    fn test_PCA_complex_whiten_c32() {
+        let X = complex_data(300, 5).mapv(|x| c32::new(x.re as f32, x.im as f32));
+        let model: PCA<c32> = PCA::fit(PCAHyperParams::new(Components::Count(3)).whiten(true).build(), &X);
+
+        // the whitened scores have an identity Hermitian covariance
+        let Z = model.transform(&X);
+        let covariance = conjugate_transpose(&Z).dot(&Z) / c32::new(299.0, 0.0);
+        assert!((covariance - Array2::<c32>::eye(3)).iter().all(|x| x.norm() < 1e-3));
+
+        let reconstruction = model.inverse_transform(&Z);
+        let scale = X.iter().map(|x| x.norm()).fold(0f32, f32::max);
+        assert!((reconstruction - &X).iter().all(|x| x.norm() < 0.05 * scale));
+    }
+}
@@ -0,0 +1,229 @@
+#![allow(non_snake_case, non_camel_case_types)]
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// How many principal components `PCA::fit` should retain.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Components {
+    /// Keep exactly this many components, at most min(n_samples, n_features).
+    Count(usize),
+    /// Keep the smallest number of components whose cumulative explained
+    /// variance ratio exceeds the given fraction, which must lie in (0, 1].
+    VarianceRatio(f64),
+    /// Keep every component, i.e. min(n_samples, n_features) of them.
+    All,
+    /// Pick the number of components with Minka's MLE of the data dimensionality.
+    /// Requires n_samples >= n_features.
+    Mle,
+    /// Keep the components whose singular value is greater than the threshold.
+    SingularValueThreshold(f64),
+}
+
+impl Components {
+    /// Check that the selection is usable on data of shape (n_samples, n_features).
+    pub(crate) fn validate(&self, n_samples: usize, n_features: usize) -> Result<()> {
+        let max_components = n_samples.min(n_features);
+        match *self {
+            Components::Count(k) => {
+                if k == 0 {
+                    return Err(Error::InvalidHyperParameter("`Components::Count` cannot be 0!".to_string()));
+                }
+                if k > max_components {
+                    return Err(Error::TooManyComponents { requested: k, max: max_components });
+                }
+            }
+            Components::VarianceRatio(ratio) => {
+                if !(ratio > 0.0 && ratio <= 1.0) {
+                    return Err(Error::InvalidHyperParameter(
+                        "`Components::VarianceRatio` must lie in (0, 1]!".to_string(),
+                    ));
+                }
+            }
+            Components::All => {}
+            Components::Mle => {
+                if n_samples < n_features {
+                    return Err(Error::InvalidHyperParameter(
+                        "`Components::Mle` requires n_samples >= n_features!".to_string(),
+                    ));
+                }
+            }
+            Components::SingularValueThreshold(threshold) => {
+                if threshold.is_nan() || threshold < 0.0 {
+                    return Err(Error::InvalidHyperParameter(
+                        "`Components::SingularValueThreshold` must be non-negative!".to_string(),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The SVD routine used by `PCA::fit`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SvdSolver {
+    /// Exact SVD of the whole (centered) data matrix through LAPACK.
+    Full,
+    /// Randomized range finder SVD (Halko et al.), only computing the requested
+    /// components. The range is sampled with `n_components + n_oversamples`
+    /// gaussian vectors and refined with `n_power_iterations` power iterations,
+    /// 10 and 4 are sensible values. Requires `Components::Count`. The only
+    /// solver of `PCA::fit_sparse`.
+    Randomized { n_oversamples: usize, n_power_iterations: usize },
+    /// Symmetric eigendecomposition of the feature covariance, or of the Gram
+    /// matrix when there are fewer samples than features. Much cheaper than
+    /// the SVD on very tall or very wide data, at the price of the accuracy of
+    /// the components with a tiny explained variance.
+    Covariance,
+    /// `Covariance` when one side of the data is at least ten times the other
+    /// and the smaller side has at most 1000 entries, `Full` otherwise.
+    Auto,
+}
+
+impl SvdSolver {
+    /// The solver `PCA::fit` actually uses on data of shape (n_samples, n_features).
+    pub fn resolve(&self, n_samples: usize, n_features: usize) -> SvdSolver {
+        match *self {
+            SvdSolver::Auto => {
+                let (short, long) = (n_samples.min(n_features), n_samples.max(n_features));
+                if short <= 1000 && long >= 10 * short {
+                    SvdSolver::Covariance
+                } else {
+                    SvdSolver::Full
+                }
+            }
+            solver => solver,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PCAHyperParams {
+    /// n_components - which principal components to keep
+    pub(crate) n_components: Components,
+    /// whiten - scale the projections to unit variance, default = false
+    pub(crate) whiten: bool,
+    /// center - subtract the columnar means before decomposing, default = true
+    pub(crate) center: bool,
+    /// solver - the SVD routine used during fit, default = SvdSolver::Full
+    pub(crate) solver: SvdSolver,
+    /// random_seed - seed for the randomized solvers, default = 0
+    pub(crate) random_seed: u64,
+}
+
+/// An helper struct used to construct a set of [valid hyperparameters]
+pub struct PCAHyperParamsBuilder {
+    n_components: Components,
+    whiten: bool,
+    center: bool,
+    solver: SvdSolver,
+    random_seed: u64,
+}
+
+impl PCAHyperParamsBuilder {
+
+    /// Set the value of `whiten`.
+    ///
+    /// When set, the projections returned by `transform` are divided by the
+    /// square root of the explained variance of each component
+    pub fn whiten(mut self, whiten: bool) -> Self {
+        self.whiten = whiten;
+        self
+    }
+
+    /// Set the value of `center`.
+    ///
+    /// Disabling centering decomposes the raw data matrix, the fitted mean
+    /// is then zero
+    pub fn center(mut self, center: bool) -> Self {
+        self.center = center;
+        self
+    }
+
+    /// Set the value of `solver`.
+    pub fn solver(mut self, solver: SvdSolver) -> Self {
+        self.solver = solver;
+        self
+    }
+
+    /// Set the value of `random_seed`.
+    ///
+    /// The seed makes the randomized solvers reproducible
+    pub fn random_seed(mut self, random_seed: u64) -> Self {
+        self.random_seed = random_seed;
+        self
+    }
+
+    /// Return an instance of `PCAHyperParams` after
+    /// having performed validation checks on all the specified hyperparamters.
+    ///
+    /// **Panics** if any of the validation checks fails.
+    pub fn build(self) -> PCAHyperParams {
+        match self.try_build() {
+            Ok(hyperparameters) => hyperparameters,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Return an instance of `PCAHyperParams` after
+    /// having performed validation checks on all the specified hyperparamters,
+    /// or the first check that failed.
+    pub fn try_build(self) -> Result<PCAHyperParams> {
+        PCAHyperParams::build(self.n_components, self.whiten, self.center, self.solver, self.random_seed)
+    }
+}
+
+impl PCAHyperParams {
+    /// `new` lets us configure our PCA parameters:
+    /// * `n_components` selects the principal components kept by `fit`.
+    ///
+    /// `n_components` is mandatory.
+    ///
+    /// Defaults are provided if optional parameters are not specified:
+    /// * `whiten = false`;
+    /// * `center = true`;
+    /// * `solver = SvdSolver::Full`;
+    /// * `random_seed = 0`.
+    pub fn new(n_components: Components) -> PCAHyperParamsBuilder {
+        PCAHyperParamsBuilder {
+            n_components,
+            whiten: false,
+            center: true,
+            solver: SvdSolver::Full,
+            random_seed: 0,
+        }
+    }
+
+    /// Which principal components are kept by `fit`.
+    pub fn n_components(&self) -> Components { self.n_components }
+
+    /// Whether projections are scaled to unit variance.
+    pub fn whiten(&self) -> bool { self.whiten }
+
+    /// Whether the data is centered before being decomposed.
+    pub fn center(&self) -> bool { self.center }
+
+    /// The SVD routine used during fit.
+    pub fn solver(&self) -> SvdSolver { self.solver }
+
+    /// The seed used by the randomized solvers.
+    pub fn random_seed(&self) -> u64 { self.random_seed }
+
+    fn build(n_components: Components, whiten: bool, center: bool, solver: SvdSolver, random_seed: u64) -> Result<Self> {
+        // the shape dependent checks happen in `fit`
+        n_components.validate(usize::MAX, usize::MAX)?;
+        let randomized = matches!(solver, SvdSolver::Randomized { .. });
+        if randomized && !matches!(n_components, Components::Count(_)) {
+            return Err(Error::InvalidHyperParameter(
+                "`SvdSolver::Randomized` requires `Components::Count`!".to_string(),
+            ));
+        }
+        Ok(PCAHyperParams {
+            n_components,
+            whiten,
+            center,
+            solver,
+            random_seed,
+        })
+    }
+}
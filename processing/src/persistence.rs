@@ -0,0 +1,173 @@
+#![allow(non_snake_case, non_camel_case_types)]
+use crate::error::{Error, Result};
+use crate::float::{Element, Float};
+use crate::pca::PCA;
+use crate::rpca::rPCA;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Version of the layout written by `Persist`, bumped whenever the fields of a
+/// persisted model change.
+pub const FORMAT_VERSION: u32 = 4;
+
+/// The versioned layout of a saved model. `format_version` comes first so that
+/// it can be read before the model itself.
+#[derive(Serialize)]
+struct Envelope<'a, M> {
+    format_version: u32,
+    model: &'a M,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    format_version: u32,
+}
+
+#[derive(Deserialize)]
+struct OwnedEnvelope<M> {
+    #[allow(dead_code)]
+    format_version: u32,
+    model: M,
+}
+
+/// Saving and loading of fitted models, as JSON or as a compact binary encoding.
+///
+/// Both forms hold the format version, the fitted arrays and the hyperparameters,
+/// and a loaded model gives the same results as the model that was saved.
+pub trait Persist: Serialize + DeserializeOwned {
+    /// Serialize the model to a JSON string.
+    fn to_json(&self) -> Result<String> {
+        let envelope = Envelope { format_version: FORMAT_VERSION, model: self };
+        serde_json::to_string(&envelope).map_err(|err| Error::Serialization(err.to_string()))
+    }
+
+    /// Load a model saved by `to_json`.
+    ///
+    /// Fails if `json` is malformed or was written with another format version.
+    fn from_json(json: &str) -> Result<Self> {
+        let header: Header = serde_json::from_str(json).map_err(|err| Error::Serialization(err.to_string()))?;
+        check_version(header.format_version)?;
+        let envelope: OwnedEnvelope<Self> =
+            serde_json::from_str(json).map_err(|err| Error::Serialization(err.to_string()))?;
+        Ok(envelope.model)
+    }
+
+    /// Serialize the model to bytes with `bincode`.
+    fn to_bytes(&self) -> Result<Vec<u8>> {
+        let envelope = Envelope { format_version: FORMAT_VERSION, model: self };
+        bincode::serialize(&envelope).map_err(|err| Error::Serialization(err.to_string()))
+    }
+
+    /// Load a model saved by `to_bytes`.
+    ///
+    /// Fails if `bytes` are truncated or malformed or were written with another
+    /// format version.
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let header: Header = bincode::deserialize(bytes).map_err(|err| Error::Serialization(err.to_string()))?;
+        check_version(header.format_version)?;
+        let envelope: OwnedEnvelope<Self> =
+            bincode::deserialize(bytes).map_err(|err| Error::Serialization(err.to_string()))?;
+        Ok(envelope.model)
+    }
+}
+
+fn check_version(found: u32) -> Result<()> {
+    if found != FORMAT_VERSION {
+        return Err(Error::FormatVersion { expected: FORMAT_VERSION, found });
+    }
+    Ok(())
+}
+
+impl<A: Element> Persist for PCA<A> {}
+
+impl<A: Float> Persist for rPCA<A> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pca::{Components, PCAHyperParams};
+    use crate::rpca::rPCAHyperParams;
+    use ndarray::{Array, Array2};
+    use ndarray_linalg::c64;
+    use ndarray_rand::RandomExt;
+    use ndarray_rand::rand_distr::Uniform;
+
+    #[test]
+    fn test_Persist_PCA() {
+        let X: Array2<f64> = Array::random((50, 6), Uniform::new(-10., 10.));
+        let hyperparams = PCAHyperParams::new(Components::Count(3)).whiten(true).build();
+        let model = PCA::fit(hyperparams, &X);
+        let expected = model.transform(&X);
+
+        // both encodings restore the model bit for bit
+        let from_json = PCA::<f64>::from_json(&model.to_json().unwrap()).unwrap();
+        let from_bytes = PCA::<f64>::from_bytes(&model.to_bytes().unwrap()).unwrap();
+        for loaded in &[from_json, from_bytes] {
+            assert_eq!(loaded.hyperparameters(), model.hyperparameters());
+            assert_eq!(loaded.components(), model.components());
+            assert_eq!(loaded.explained_variance(), model.explained_variance());
+            assert_eq!(loaded.transform(&X), expected);
+        }
+
+        // complex models too
+        let Z: Array2<c64> = X.mapv(|x| c64::new(x, -x / 2.0));
+        let complex: PCA<c64> = PCA::fit(PCAHyperParams::new(Components::All).build(), &Z);
+        let loaded = PCA::<c64>::from_bytes(&complex.to_bytes().unwrap()).unwrap();
+        assert_eq!(loaded.transform(&Z), complex.transform(&Z));
+    }
+
+    #[test]
+    fn test_Persist_rPCA() {
+        let X: Array2<f32> = Array::random((20, 10), Uniform::new(-1f32, 1.));
+        let hyperparams = rPCAHyperParams::new(20, 10).max_n_iterations(50).build();
+        let model = rPCA::fit(hyperparams, &X);
+
+        let loaded = rPCA::<f32>::from_json(&model.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.L(), model.L());
+        assert_eq!(loaded.S(), model.S());
+        assert_eq!(loaded.N(), model.N());
+        assert_eq!(loaded.report(), model.report());
+        assert_eq!(loaded.hyperparameters(), model.hyperparameters());
+    }
+
+    #[test]
+    fn test_rPCAHyperParams_baseline_config() {
+        // the configuration layout from before the solver options were added
+        let json = r#"{"lambda":0.1,"mu":2.0,"tolerance":1e-7,"max_n_iterations":500}"#;
+        let loaded: rPCAHyperParams = serde_json::from_str(json).unwrap();
+        let expected = rPCAHyperParams::new(100, 100).mu(2.0).tolerance(1e-7).max_n_iterations(500).build();
+        assert_eq!(loaded, expected);
+        assert_eq!(loaded.mu_max(), 2.0e7);
+
+        // and a current configuration round-trips unchanged
+        let saved = rPCAHyperParams::new(100, 100).rho(1.5).mu_max(50.0).delta(0.1).build();
+        let loaded: rPCAHyperParams = serde_json::from_str(&serde_json::to_string(&saved).unwrap()).unwrap();
+        assert_eq!(loaded, saved);
+    }
+
+    #[test]
+    fn test_Persist_errors() {
+        let X: Array2<f64> = Array::random((10, 3), Uniform::new(-1., 1.));
+        let model = PCA::fit(PCAHyperParams::new(Components::All).build(), &X);
+
+        let json = model.to_json().unwrap().replacen("\"format_version\":4", "\"format_version\":1", 1);
+        assert!(matches!(
+            PCA::<f64>::from_json(&json),
+            Err(Error::FormatVersion { expected: 4, found: 1 })
+        ));
+
+        let mut bytes = model.to_bytes().unwrap();
+        bytes[0] = 7;
+        assert!(matches!(
+            PCA::<f64>::from_bytes(&bytes),
+            Err(Error::FormatVersion { expected: 4, found: 7 })
+        ));
+
+        let bytes = model.to_bytes().unwrap();
+        assert!(matches!(
+            PCA::<f64>::from_bytes(&bytes[..bytes.len() / 2]),
+            Err(Error::Serialization(_))
+        ));
+        assert!(matches!(PCA::<f64>::from_json("{}"), Err(Error::Serialization(_))));
+    }
+}
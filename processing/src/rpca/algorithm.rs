@@ -0,0 +1,811 @@
+#![allow(non_snake_case, non_camel_case_types)]
+extern crate ndarray;
+extern crate ndarray_linalg;
+extern crate openblas_src;
+
+use crate::error::{check_input, Error, Result};
+use crate::float::Float;
+use crate::linalg::randomized_svd;
+use crate::rpca::hyperparameters::{Corruption, rPCAHyperParams, rPCASolver, SvtSolver};
+use crate::rpca::report::FitReport;
+use crate::rpca::solvers::{alt_proj, apg, gradient_descent};
+use ndarray::{ArrayBase, Array2, ArrayView2, Axis, Data, Ix2, Zip, s};
+use ndarray_linalg::{SVD};
+use ndarray_rand::rand::{Rng, SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct rPCA<A: Float = f64> {
+    L: Array2<A>,
+    S: Array2<A>,
+    N: Array2<A>,
+    report: FitReport,
+    hyperparameters: rPCAHyperParams,
+}
+
+/// rPCA method based on https://statistics.stanford.edu/sites/g/files/sbiybj6031/f/2009-13.pdf
+/// with matlab implementation, https://github.com/dlaptev/RobustPCA
+impl<A: Float> rPCA<A> {
+    /// Decompose `X` into a low-rank matrix `L` and a sparse matrix `S`.
+    ///
+    /// **Panics** if `try_fit` returns an error.
+    pub fn fit (
+        hyperparameters: rPCAHyperParams,
+        X : &ArrayBase<impl Data<Elem = A>, Ix2>,
+    ) -> Self {
+        match rPCA::try_fit(hyperparameters, X) {
+            Ok(model) => model,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Decompose `X` into a low-rank matrix `L` and a sparse matrix `S`.
+    ///
+    /// Fails if `X` is empty or has non-finite values, if the target rank of
+    /// the solver is larger than min(n, m) or if an SVD fails.
+    pub fn try_fit (
+        hyperparameters: rPCAHyperParams,
+        X : &ArrayBase<impl Data<Elem = A>, Ix2>,
+    ) -> Result<Self> {
+        check_input(X, 1)?;
+        let (_n, _m) = X.dim();
+        let (L, S, report) = match hyperparameters.solver {
+            rPCASolver::Admm => return rPCA::admm(hyperparameters, X, None),
+            rPCASolver::Apg => apg(&hyperparameters, X)?,
+            rPCASolver::AltProj { rank } | rPCASolver::GradientDescent { rank, .. } if rank > _n.min(_m) => {
+                return Err(Error::TooManyComponents { requested: rank, max: _n.min(_m) });
+            }
+            rPCASolver::AltProj { rank } => alt_proj(&hyperparameters, X, rank)?,
+            rPCASolver::GradientDescent { rank, sparsity } => gradient_descent(&hyperparameters, X, rank, sparsity)?,
+        };
+        Ok(Self {
+            L,
+            S,
+            N: Array2::zeros(X.dim()),
+            report,
+            hyperparameters,
+        })
+    }
+
+    /// Robust matrix completion, decompose `X` into `L + S` on the entries where
+    /// `mask` is true only, Candès et al. section 1.6. `L` is completed on the
+    /// unobserved entries, which may hold any value, NaN included, and where `S` is zero.
+    ///
+    /// **Panics** if `try_fit_masked` returns an error.
+    pub fn fit_masked (
+        hyperparameters: rPCAHyperParams,
+        X : &ArrayBase<impl Data<Elem = A>, Ix2>,
+        mask : &ArrayBase<impl Data<Elem = bool>, Ix2>,
+    ) -> Self {
+        match rPCA::try_fit_masked(hyperparameters, X, mask) {
+            Ok(model) => model,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Robust matrix completion, decompose `X` into `L + S` on the entries where
+    /// `mask` is true only.
+    ///
+    /// Fails if the solver is not `rPCASolver::Admm`, if `X` is empty, if `mask`
+    /// does not have the shape of `X`, if an observed entry is not finite or if
+    /// an SVD fails.
+    pub fn try_fit_masked (
+        hyperparameters: rPCAHyperParams,
+        X : &ArrayBase<impl Data<Elem = A>, Ix2>,
+        mask : &ArrayBase<impl Data<Elem = bool>, Ix2>,
+    ) -> Result<Self> {
+        if hyperparameters.solver != rPCASolver::Admm {
+            return Err(Error::InvalidHyperParameter("masks are only supported by `rPCASolver::Admm`!".to_string()));
+        }
+        let (_n, _m) = X.dim();
+        if _n == 0 || _m == 0 {
+            return Err(Error::EmptyInput);
+        }
+        if mask.dim() != X.dim() {
+            return Err(Error::MaskShape { expected: X.dim(), found: mask.dim() });
+        }
+        if X.iter().zip(mask.iter()).any(|(x, &observed)| observed && !x.is_finite()) {
+            return Err(Error::NonFiniteValue);
+        }
+        // the unobserved values never enter the fit, zero them so that they cannot poison it
+        let X = Array2::from_shape_fn(X.dim(), |ij| if mask[ij] { X[ij] } else { A::zero() });
+        rPCA::admm(hyperparameters, &X, Some(mask.view()))
+    }
+
+    /// The inexact ALM iterations, only enforcing `L + S = X` where `observed` is true
+    fn admm (
+        hyperparameters: rPCAHyperParams,
+        X : &ArrayBase<impl Data<Elem = A>, Ix2>,
+        observed : Option<ArrayView2<bool>>,
+    ) -> Result<Self> {
+        let (_n, _m) = X.dim();
+
+        let normX = frobenius_norm(X);
+
+        let mut L = Array2::zeros(X.dim()) as Array2<A>;
+        let mut S = Array2::zeros(X.dim()) as Array2<A>;
+        let mut Y = Array2::zeros(X.dim()) as Array2<A>;
+        let mut Z = Array2::zeros(X.dim()) as Array2<A>;
+        // free on the unobserved entries, where it absorbs the residual
+        let mut E = Array2::zeros(X.dim()) as Array2<A>;
+        // dense noise of the observed entries, ||N||_F <= delta
+        let mut N = Array2::zeros(X.dim()) as Array2<A>;
+        let delta = A::real(hyperparameters.delta);
+
+        let mut tempDo = Array2::zeros(X.dim()) as Array2<A>;
+        let mut tempLo = Array2::zeros(X.dim()) as Array2<A>;
+        let mut residuals = Vec::new();
+        let mut rank = 0;
+        let mut converged = false;
+        let mut mu = A::real(hyperparameters.mu);
+        let (lambda, rho, mu_max) = (
+            A::real(hyperparameters.lambda),
+            A::real(hyperparameters.rho),
+            A::real(hyperparameters.mu_max),
+        );
+        // predicted rank of L for the partial SVD
+        let max_rank = _n.min(_m);
+        let mut sv = max_rank.min(10);
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..hyperparameters.max_n_iterations {
+            let tempY = Y.map(|&x| x / mu);
+
+            // ADMM step, update L and S
+
+            Zip::from(&mut tempDo)
+                .and(X)
+                .and(&S)
+                .and(&E)
+                .and(&N)
+                .and(&tempY)
+                .apply(|a, &b, &c, &e, &f, &d| {
+                    *a = b - c - e - f + d;
+                });
+            let (newL, newRank) = match hyperparameters.svt_solver {
+                SvtSolver::Full => svt(mu.recip(), &tempDo)?,
+                SvtSolver::Partial => svt_partial(mu.recip(), &tempDo, sv, &mut rng)?,
+            };
+            L = newL;
+            rank = newRank;
+            sv = if rank < sv {
+                max_rank.min(rank + 1)
+            } else {
+                max_rank.min(rank + (max_rank / 20).max(1))
+            };
+
+            Zip::from(&mut tempLo)
+                .and(X)
+                .and(&L)
+                .and(&N)
+                .and(&tempY)
+                .apply(|a, &b, &c, &f, &d| {
+                    *a = b - c - f + d;
+                });
+            // the unobserved entries go to E, leaving S zero there
+            if let Some(mask) = observed {
+                Zip::from(&mut E)
+                    .and(&mut tempLo)
+                    .and(&mask)
+                    .apply(|e, t, &o| {
+                        if !o {
+                            *e = *t;
+                            *t = A::zero();
+                        }
+                    });
+            }
+            S = match hyperparameters.corruption {
+                Corruption::Entries => So(lambda / mu, &tempLo),
+                Corruption::Rows => group_shrinkage(lambda / mu, &tempLo, Axis(0)),
+                Corruption::Columns => group_shrinkage(lambda / mu, &tempLo, Axis(1)),
+            };
+
+            // stable PCP, project what L and S leave of the observed entries on
+            // the Frobenius ball of radius delta
+            if delta > A::zero() {
+                Zip::from(&mut N)
+                    .and(X)
+                    .and(&L)
+                    .and(&S)
+                    .and(&E)
+                    .and(&tempY)
+                    .apply(|a, &b, &c, &d, &e, &y| {
+                        *a = b - c - d - e + y;
+                    });
+                let norm = frobenius_norm(&N);
+                if norm > delta {
+                    N *= delta / norm;
+                }
+            }
+
+            // and augmented lagrangian multiplier
+            Zip::from(&mut Z)
+                .and(X)
+                .and(&L)
+                .and(&S)
+                .and(&E)
+                .and(&N)
+                .apply(|a, &b, &c, &d, &e, &f| {
+                    *a = b - c - d - e - f;
+                });
+            Y = Y + Z.map(|&x| x * mu);
+            mu = (mu * rho).min(mu_max);
+
+            // an all zero X is decomposed exactly by L = S = 0
+            let err = if normX > A::zero() {
+                (frobenius_norm(&Z) / normX).to_f64().expect("a float converts to f64")
+            } else {
+                0.0
+            };
+            residuals.push(err);
+
+            if err < hyperparameters.tolerance {
+               converged = true;
+               break
+            }
+        }
+
+        let report = FitReport {
+            n_iterations: residuals.len() as u64,
+            converged,
+            residuals,
+            rank,
+            nnz: S.iter().filter(|&&x| x != A::zero()).count(),
+        };
+
+        Ok(Self {
+            L,
+            S,
+            N,
+            report,
+            hyperparameters,
+        })
+    }
+
+    pub fn L(&self) -> &Array2<A> { &self.L }
+
+    pub fn S(&self) -> &Array2<A> { &self.S }
+
+    /// The dense noise of stable PCP, zero unless `delta` is positive.
+    pub fn N(&self) -> &Array2<A> { &self.N }
+
+    /// The indices of the rows of `S` that are not zero, the samples flagged as
+    /// outliers by `Corruption::Rows`, or of its columns for `Corruption::Columns`.
+    /// With `Corruption::Entries` these are the samples with a corrupted entry.
+    pub fn outliers(&self) -> Vec<usize> {
+        let axis = match self.hyperparameters.corruption {
+            Corruption::Columns => Axis(1),
+            Corruption::Entries | Corruption::Rows => Axis(0),
+        };
+        self.S.axis_iter(axis)
+            .enumerate()
+            .filter(|(_, lane)| lane.iter().any(|&x| x != A::zero()))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Convergence diagnostics of the ADMM loop.
+    pub fn report(&self) -> &FitReport { &self.report }
+
+    /// The hyperparameters used during `fit`.
+    pub fn hyperparameters(&self) -> &rPCAHyperParams { &self.hyperparameters }
+}
+
+
+/// Shrinkage Operator for Singular Values
+///
+/// **Panics** if the SVD of `X` fails.
+pub fn Do<A: Float> (
+    tau : A,
+    X : &ArrayBase<impl Data<Elem = A>, Ix2>,
+) -> Array2<A> {
+    match try_Do(tau, X) {
+        Ok(r) => r,
+        Err(err) => panic!("{}", err),
+    }
+}
+
+/// Shrinkage Operator for Singular Values, failing if the SVD of `X` fails
+pub fn try_Do<A: Float> (
+    tau : A,
+    X : &ArrayBase<impl Data<Elem = A>, Ix2>,
+) -> Result<Array2<A>> {
+    svt(tau, X).map(|(r, _)| r)
+}
+
+/// Singular value thresholding, also returning the rank of the result
+pub(crate) fn svt<A: Float> (
+    tau : A,
+    X : &ArrayBase<impl Data<Elem = A>, Ix2>,
+) -> Result<(Array2<A>, usize)> {
+    let temp:Array2<A> = X.to_owned();
+    let (u, s, v) = temp.svd(true, true)?;
+    let k = s.len();
+    let s = Array2::from_diag(&s);
+    let so = So(tau, &s);
+    let u = u.expect("U was requested from the SVD");
+    let v = v.expect("V^T was requested from the SVD");
+    // only the first min(n, m) columns of U and rows of V^T pair with a singular value
+    let rank = so.diag().iter().filter(|&&x| x != A::zero()).count();
+    let mut r = u.slice(s![.., ..k]).dot(&so);
+    r = r.dot(&v.slice(s![..k, ..]));
+    Ok((r, rank))
+}
+
+/// Shrinkage Operator
+///
+/// Elementwise soft-thresholding, out[i, j] = sign(x[i, j]) * max(|x[i, j]| - tau, 0)
+pub fn So<A: Float> (
+    tau : A,
+    X : &ArrayBase<impl Data<Elem = A>, Ix2>,
+) -> Array2<A> {
+    let temp = X.map(|x| (x.abs() - tau).max(A::zero()));
+    sign(X) * temp
+}
+
+/// Group Shrinkage Operator, the proximal operator of the L2,1 norm
+///
+/// Every lane of `X` along `axis`, its rows for `Axis(0)` and its columns for
+/// `Axis(1)`, is scaled by max(1 - tau / ||lane||_2, 0)
+pub fn group_shrinkage<A: Float> (
+    tau : A,
+    X : &ArrayBase<impl Data<Elem = A>, Ix2>,
+    axis : Axis,
+) -> Array2<A> {
+    let mut out = X.to_owned();
+    for mut lane in out.axis_iter_mut(axis) {
+        let norm = lane.iter().map(|x| x.powi(2)).fold(A::zero(), |a, b| a + b).sqrt();
+        let scale = if norm > tau { A::one() - tau / norm } else { A::zero() };
+        lane *= scale;
+    }
+    out
+}
+
+/// Singular value thresholding computing only the `sv` leading singular triplets,
+/// falling back to the full SVD once `sv` is a large fraction of the spectrum
+pub(crate) fn svt_partial<A: Float, R: Rng> (
+    tau : A,
+    X : &ArrayBase<impl Data<Elem = A>, Ix2>,
+    sv : usize,
+    rng : &mut R,
+) -> Result<(Array2<A>, usize)> {
+    let (_n, _m) = X.dim();
+    if 2 * sv > _n.min(_m) {
+        return svt(tau, X);
+    }
+    let (u, s, v) = randomized_svd(X, sv, 10, 4, rng)?;
+    let s = Array2::from_diag(&s);
+    let so = So(tau, &s);
+    let rank = so.diag().iter().filter(|&&x| x != A::zero()).count();
+    Ok((u.dot(&so).dot(&v), rank))
+}
+
+/// returns array the same size as X where
+/// out[i, j] = 1 if x[i, j] > 0
+/// out[i, j] = 0 if x[i, j] = 0
+/// out[i, j] = -1 if x[i, j] < 0
+pub fn sign<A: Float> (
+    X: &ArrayBase<impl Data<Elem=A>, Ix2>,
+) -> Array2<A> {
+    let (_n, _m) = X.dim();
+    let mut out = X.to_owned();
+    Zip::from(&mut out)
+                .and(X)
+                .apply(|a, &b| {
+                    if b == A::zero() {
+                        *a = A::zero();
+                    }
+                    else if b > A::zero() {
+                        *a = A::one();
+                    }
+                    else {
+                        *a = -A::one();
+                    }});
+    out
+}
+
+pub fn frobenius_norm<A: Float> (
+    x: &ArrayBase<impl Data<Elem = A>, Ix2>,
+) -> A {
+    x.map(|x| x.powi(2)).sum().sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{Array, Array2};
+    use ndarray_rand::RandomExt;
+    use ndarray_rand::rand_distr::{StandardNormal, Uniform};
+    use ndarray::arr2;
+    use ndarray_rand::rand::{SeedableRng, rngs::StdRng};
+    use crate::rPCAHyperParams;
+
+    /// A rank `rank` matrix plus a sparse matrix with roughly `density` of its
+    /// entries corrupted by large values
+    fn low_rank_plus_sparse(
+        n : usize,
+        m : usize,
+        rank : usize,
+        density : f64,
+        seed : u64,
+    ) -> (Array2<f64>, Array2<f64>) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let A: Array2<f64> = Array::random_using((n, rank), Uniform::new(-1., 1.), &mut rng);
+        let B: Array2<f64> = Array::random_using((rank, m), Uniform::new(-1., 1.), &mut rng);
+        let L = A.dot(&B);
+        let mask: Array2<f64> = Array::random_using((n, m), Uniform::new(0., 1.), &mut rng);
+        let values: Array2<f64> = Array::random_using((n, m), Uniform::new(-5., 5.), &mut rng);
+        let mut S = Array2::zeros((n, m));
+        Zip::from(&mut S).and(&mask).and(&values).apply(|s, &p, &v| {
+            if p < density {
+                *s = v;
+            }
+        });
+        (L, S)
+    }
+
+    #[test]
+    fn test_rPCA() {
+
+        // Let's setup a synthetic set of observations, composed of two clusters with known means
+        let X: Array2<f64> = Array::random((100, 100), Uniform::new(-100., 100.));
+        let (_n, _m) = X.dim();
+        let hyperparams = rPCAHyperParams::new(_n as u64, _m as u64).build();
+
+        let _model = rPCA::fit(hyperparams, &X);
+
+        /*
+        // Does it work?
+        let centroids = compute_centroids(2, &observations, &memberships);
+        assert_abs_diff_eq!(
+            centroids.index_axis(Axis(0), 0),
+            expected_centroid_1,
+            epsilon = 1e-5
+        );
+        assert_abs_diff_eq!(
+            centroids.index_axis(Axis(0), 1),
+            expected_centroid_2,
+            epsilon = 1e-5
+        );
+
+        assert_eq!(centroids.len_of(Axis(0)), 2);
+        */
+    }
+
+    #[test]
+    fn test_rPCA_try_fit_errors() {
+        let X: Array2<f64> = Array2::zeros((0, 4));
+        let hyperparams = rPCAHyperParams::new(1, 4).build();
+        assert!(matches!(rPCA::try_fit(hyperparams.clone(), &X), Err(Error::EmptyInput)));
+
+        let mut X: Array2<f64> = Array2::ones((3, 4));
+        X[[1, 2]] = f64::NAN;
+        assert!(matches!(rPCA::try_fit(hyperparams, &X), Err(Error::NonFiniteValue)));
+
+        assert!(matches!(
+            rPCAHyperParams::new(3, 4).tolerance(0.0).try_build(),
+            Err(Error::InvalidHyperParameter(_))
+        ));
+        assert!(matches!(
+            rPCAHyperParams::new(3, 4).lambda(-0.1).try_build(),
+            Err(Error::InvalidHyperParameter(_))
+        ));
+        assert!(matches!(
+            rPCAHyperParams::new(3, 4).mu(-1.0).mu_max(-1.0).try_build(),
+            Err(Error::InvalidHyperParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_So() {
+        let X = arr2(&[[3.0, -0.5], [-2.0, 1.0]]);
+        let out = So(1.0, &X);
+        assert_eq!(out, arr2(&[[2.0, 0.0], [-1.0, 0.0]]));
+    }
+
+    #[test]
+    fn test_Do() {
+        // singular values of a diagonal matrix are its absolute diagonal entries
+        let X = arr2(&[[4.0, 0.0, 0.0], [0.0, -2.0, 0.0]]);
+        let out = Do(1.0, &X);
+        let expected = arr2(&[[3.0, 0.0, 0.0], [0.0, -1.0, 0.0]]);
+        let err = (&out - &expected).map(|x: &f64| x.abs()).fold(0.0_f64, |a, &b| a.max(b));
+        assert!(err < 1e-10);
+    }
+
+    #[test]
+    fn test_rPCA_recovers_low_rank_plus_sparse() {
+        let (L0, S0) = low_rank_plus_sparse(60, 50, 2, 0.05, 7);
+        let X = &L0 + &S0;
+        let hyperparams = rPCAHyperParams::new(60, 50).build();
+
+        let model = rPCA::fit(hyperparams, &X);
+
+        let err_L = frobenius_norm(&(model.L() - &L0)) / frobenius_norm(&L0);
+        let err_S = frobenius_norm(&(model.S() - &S0)) / frobenius_norm(&S0);
+        assert!(err_L < 1e-3, "relative error on L is {}", err_L);
+        assert!(err_S < 1e-3, "relative error on S is {}", err_S);
+    }
+
+    #[test]
+    fn test_rPCA_adaptive_mu() {
+        let (L0, S0) = low_rank_plus_sparse(60, 60, 2, 0.05, 3);
+        let X = &L0 + &S0;
+
+        // start the growing schedule from 1.25 / ||X||_2
+        let (_, sigma, _) = X.svd(false, false).unwrap();
+        let adaptive = rPCAHyperParams::new(60, 60)
+            .mu(1.25 / sigma[0])
+            .rho(1.5)
+            .build();
+
+        let fixed = rPCA::fit(rPCAHyperParams::new(60, 60).fixed_mu().build(), &X);
+        let adaptive = rPCA::fit(adaptive, &X);
+        assert!(fixed.report().converged());
+        assert!(adaptive.report().converged());
+        assert!(adaptive.report().n_iterations() < fixed.report().n_iterations());
+
+        let err_L = frobenius_norm(&(adaptive.L() - &L0)) / frobenius_norm(&L0);
+        assert!(err_L < 1e-3, "relative error on L is {}", err_L);
+
+        assert!(matches!(
+            rPCAHyperParams::new(60, 60).rho(0.5).try_build(),
+            Err(Error::InvalidHyperParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_rPCA_partial_svt() {
+        let (L0, S0) = low_rank_plus_sparse(120, 80, 3, 0.05, 5);
+        let X = &L0 + &S0;
+
+        let full = rPCA::fit(rPCAHyperParams::new(120, 80).build(), &X);
+        let partial = rPCA::fit(
+            rPCAHyperParams::new(120, 80).svt_solver(SvtSolver::Partial).build(),
+            &X,
+        );
+        assert!(partial.report().converged());
+        assert_eq!(partial.report().rank(), 3);
+
+        let err_L = frobenius_norm(&(partial.L() - &L0)) / frobenius_norm(&L0);
+        assert!(err_L < 1e-3, "relative error on L is {}", err_L);
+        let diff = frobenius_norm(&(partial.L() - full.L())) / frobenius_norm(full.L());
+        assert!(diff < 1e-3, "partial and full SVT differ by {}", diff);
+    }
+
+    #[test]
+    fn test_rPCA_report() {
+        let (L0, S0) = low_rank_plus_sparse(40, 40, 3, 0.05, 11);
+        let X = &L0 + &S0;
+        let hyperparams = rPCAHyperParams::new(40, 40).build();
+
+        let model = rPCA::fit(hyperparams, &X);
+        let report = model.report();
+        assert!(report.converged());
+        assert!(report.n_iterations() > 0);
+        assert_eq!(report.residuals().len() as u64, report.n_iterations());
+        assert!(report.residual() < 1e-6);
+        assert_eq!(report.rank(), 3);
+        assert_eq!(report.nnz(), S0.iter().filter(|&&x| x != 0.0).count());
+
+        // hitting the iteration cap is reported as not converged
+        let hyperparams = rPCAHyperParams::new(40, 40).max_n_iterations(2).build();
+        let report = rPCA::fit(hyperparams, &X).report().clone();
+        assert!(!report.converged());
+        assert_eq!(report.n_iterations(), 2);
+    }
+
+    #[test]
+    fn test_rPCA_f32() {
+        let (L0, S0) = low_rank_plus_sparse(60, 50, 2, 0.05, 7);
+        let X = (&L0 + &S0).mapv(|x| x as f32);
+        let hyperparams = rPCAHyperParams::new(60, 50).tolerance(1e-5).build();
+
+        let model: rPCA<f32> = rPCA::fit(hyperparams, &X);
+        assert!(model.report().converged());
+
+        let L0 = L0.mapv(|x| x as f32);
+        let err_L = frobenius_norm(&(model.L() - &L0)) / frobenius_norm(&L0);
+        assert!(err_L < 1e-3, "relative error on L is {}", err_L);
+
+        // the shrinkage operators follow the element type
+        let out: Array2<f32> = So(1.0f32, &arr2(&[[3.0f32, -0.5], [-2.0, 1.0]]));
+        assert_eq!(out, arr2(&[[2.0f32, 0.0], [-1.0, 0.0]]));
+        let out: Array2<f32> = Do(1.0f32, &arr2(&[[4.0f32, 0.0], [0.0, -2.0]]));
+        assert!((out - arr2(&[[3.0f32, 0.0], [0.0, -1.0]])).iter().all(|x| x.abs() < 1e-5));
+    }
+
+    #[test]
+    fn test_rPCA_masked_completion() {
+        let (L0, S0) = low_rank_plus_sparse(60, 50, 2, 0.05, 13);
+        let mut X = &L0 + &S0;
+        let mut rng = StdRng::seed_from_u64(17);
+        let p: Array2<f64> = Array::random_using((60, 50), Uniform::new(0., 1.), &mut rng);
+        let mask = p.map(|&p| p > 0.2);
+        Zip::from(&mut X).and(&mask).apply(|x, &o| if !o { *x = f64::NAN });
+
+        let model = rPCA::fit_masked(rPCAHyperParams::new(60, 50).build(), &X, &mask);
+        assert!(model.report().converged());
+
+        // L is recovered on every entry, the unobserved ones included
+        let err_L = frobenius_norm(&(model.L() - &L0)) / frobenius_norm(&L0);
+        assert!(err_L < 1e-2, "relative error on L is {}", err_L);
+
+        // while S is only estimated on the observed entries
+        let observed_S0 = Array2::from_shape_fn((60, 50), |ij| if mask[ij] { S0[ij] } else { 0.0 });
+        let err_S = frobenius_norm(&(model.S() - &observed_S0)) / frobenius_norm(&observed_S0);
+        assert!(err_S < 1e-2, "relative error on S is {}", err_S);
+        assert!(model.S().iter().zip(mask.iter()).all(|(&s, &o)| o || s == 0.0));
+
+        // a full mask is a plain fit
+        let X = &L0 + &S0;
+        let hyperparams = rPCAHyperParams::new(60, 50).build();
+        let masked = rPCA::fit_masked(hyperparams.clone(), &X, &Array2::from_elem((60, 50), true));
+        let plain = rPCA::fit(hyperparams, &X);
+        assert!((masked.L() - plain.L()).iter().all(|x| x.abs() < 1e-12));
+    }
+
+    #[test]
+    fn test_rPCA_masked_errors() {
+        let hyperparams = rPCAHyperParams::new(3, 4).build();
+        let mut X: Array2<f64> = Array2::ones((3, 4));
+        let mut mask = Array2::from_elem((3, 4), true);
+
+        assert!(matches!(
+            rPCA::try_fit_masked(hyperparams.clone(), &X, &Array2::from_elem((4, 3), true)),
+            Err(Error::MaskShape { expected: (3, 4), found: (4, 3) })
+        ));
+        X[[1, 2]] = f64::NAN;
+        assert!(matches!(
+            rPCA::try_fit_masked(hyperparams.clone(), &X, &mask),
+            Err(Error::NonFiniteValue)
+        ));
+        mask[[1, 2]] = false;
+        assert!(rPCA::try_fit_masked(hyperparams, &X, &mask).is_ok());
+    }
+
+    #[test]
+    fn test_rPCA_stable_pcp() {
+        let (n, m, sigma) = (80, 60, 0.05);
+        let (L0, S0) = low_rank_plus_sparse(n, m, 2, 0.05, 19);
+        let mut rng = StdRng::seed_from_u64(23);
+        let noise: Array2<f64> = Array::random_using((n, m), StandardNormal, &mut rng) * sigma;
+        let X = &L0 + &S0 + &noise;
+
+        let nm = (n * m) as f64;
+        let delta = (nm + (8.0 * nm).sqrt()).sqrt() * sigma;
+        let hyperparams = rPCAHyperParams::new(n as u64, m as u64).mu(1e-2).rho(1.5).delta(delta).build();
+        let stable = rPCA::fit(hyperparams.clone(), &X);
+        assert!(stable.report().converged());
+        assert_eq!(stable.hyperparameters().delta(), delta);
+
+        // X = L + S + N with the noise inside the ball
+        assert!(frobenius_norm(stable.N()) <= delta * (1.0 + 1e-6));
+        let residual = frobenius_norm(&(&X - stable.L() - stable.S() - stable.N())) / frobenius_norm(&X);
+        assert!(residual < 1e-5);
+
+        // exact PCP pushes the dense noise into S, stable PCP keeps S sparse
+        let exact = rPCA::fit(rPCAHyperParams::new(n as u64, m as u64).mu(1e-2).rho(1.5).build(), &X);
+        assert!(exact.N().iter().all(|&x| x == 0.0));
+        let nnz = S0.iter().filter(|&&x| x != 0.0).count();
+        assert!(exact.report().nnz() > 5 * nnz);
+        assert!(stable.report().nnz() < exact.report().nnz() / 4);
+        // and the large outliers are all found
+        assert!(S0.iter().zip(stable.S().iter()).all(|(&s0, &s)| s0.abs() < 1.0 || s0 * s > 0.0));
+
+        let err_stable = frobenius_norm(&(stable.L() - &L0)) / frobenius_norm(&L0);
+        let err_exact = frobenius_norm(&(exact.L() - &L0)) / frobenius_norm(&L0);
+        assert!(err_stable < err_exact, "stable {} exact {}", err_stable, err_exact);
+        assert!(err_stable < 0.05, "relative error on L is {}", err_stable);
+
+        assert!(matches!(
+            rPCAHyperParams::new(3, 4).delta(-1.0).try_build(),
+            Err(Error::InvalidHyperParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_rPCA_solvers() {
+        let (L0, S0) = low_rank_plus_sparse(60, 50, 2, 0.05, 29);
+        let X = &L0 + &S0;
+
+        let solvers = [
+            rPCASolver::Admm,
+            rPCASolver::Apg,
+            rPCASolver::AltProj { rank: 2 },
+            rPCASolver::GradientDescent { rank: 2, sparsity: 0.15 },
+        ];
+        for &solver in &solvers {
+            let hyperparams = rPCAHyperParams::new(60, 50).solver(solver).max_n_iterations(5000).build();
+            let model = rPCA::fit(hyperparams, &X);
+            let err_L = frobenius_norm(&(model.L() - &L0)) / frobenius_norm(&L0);
+            let err_S = frobenius_norm(&(model.S() - &S0)) / frobenius_norm(&S0);
+            assert!(model.report().converged(), "{:?} did not converge", solver);
+            // every solver reports the relative residual of its decomposition
+            let residual = frobenius_norm(&(&X - model.L() - model.S())) / frobenius_norm(&X);
+            assert!((model.report().residual() - residual).abs() < 1e-12, "{:?}", solver);
+            assert_eq!(model.report().rank(), 2);
+            assert!(err_L < 1e-3, "{:?}: relative error on L is {}", solver, err_L);
+            assert!(err_S < 1e-3, "{:?}: relative error on S is {}", solver, err_S);
+            assert!(model.N().iter().all(|&x| x == 0.0));
+        }
+
+        assert!(matches!(
+            rPCA::try_fit(rPCAHyperParams::new(60, 50).solver(rPCASolver::AltProj { rank: 51 }).build(), &X),
+            Err(Error::TooManyComponents { requested: 51, max: 50 })
+        ));
+        let mask = Array2::from_elem((60, 50), true);
+        assert!(matches!(
+            rPCA::try_fit_masked(rPCAHyperParams::new(60, 50).solver(rPCASolver::Apg).build(), &X, &mask),
+            Err(Error::InvalidHyperParameter(_))
+        ));
+        for builder in [
+            rPCAHyperParams::new(60, 50).solver(rPCASolver::Apg).delta(1.0),
+            rPCAHyperParams::new(60, 50).solver(rPCASolver::Apg).mu(1.0),
+            rPCAHyperParams::new(60, 50).solver(rPCASolver::Apg).rho(1.5),
+            rPCAHyperParams::new(60, 50).solver(rPCASolver::AltProj { rank: 2 }).mu_max(1e3),
+            rPCAHyperParams::new(60, 50).solver(rPCASolver::AltProj { rank: 2 }).svt_solver(SvtSolver::Partial),
+            rPCAHyperParams::new(60, 50).solver(rPCASolver::AltProj { rank: 0 }),
+            rPCAHyperParams::new(60, 50).solver(rPCASolver::GradientDescent { rank: 2, sparsity: 1.0 }),
+        ] {
+            assert!(matches!(builder.try_build(), Err(Error::InvalidHyperParameter(_))));
+        }
+    }
+
+    #[test]
+    fn test_group_shrinkage() {
+        let X: Array2<f64> = arr2(&[[3., 4.], [0.3, 0.4]]);
+        let rows: Array2<f64> = group_shrinkage(1., &X, Axis(0));
+        assert!(rows.iter().zip(&[2.4, 3.2, 0., 0.]).all(|(a, b)| (a - b).abs() < 1e-12));
+        // columns of norms sqrt(9.09) and sqrt(16.16)
+        let columns: Array2<f64> = group_shrinkage(1., &X, Axis(1));
+        assert!((columns[[0, 0]] - 3. * (1. - 1. / 9.09f64.sqrt())).abs() < 1e-12);
+        assert!((columns[[1, 1]] - 0.4 * (1. - 1. / 16.16f64.sqrt())).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_rPCA_outlier_pursuit() {
+        let (n, m) = (100, 20);
+        let mut rng = StdRng::seed_from_u64(37);
+        let U = Array::random_using((n, 2), StandardNormal, &mut rng) as Array2<f64>;
+        let V = Array::random_using((2, m), StandardNormal, &mut rng) as Array2<f64>;
+        let L0 = U.dot(&V);
+        let corrupted = vec![3, 17, 28, 40, 55, 61, 79, 93];
+        let mut X = L0.clone();
+        for &i in &corrupted {
+            X.row_mut(i).assign(&Array::random_using(m, Uniform::new(-10., 10.), &mut rng));
+        }
+
+        // above the 3 / (7 sqrt(gamma n)) of the analysis, which flags clean samples
+        // of this small problem too
+        let lambda = 0.5;
+        let hyperparams = rPCAHyperParams::new(n as u64, m as u64)
+            .lambda(lambda)
+            .corruption(Corruption::Rows)
+            .max_n_iterations(5000)
+            .build();
+        let model = rPCA::fit(hyperparams, &X);
+        assert!(model.report().converged());
+        assert_eq!(model.outliers(), corrupted);
+        // the column space of the clean samples is recovered
+        for i in (0..n).filter(|i| !corrupted.contains(i)) {
+            let err = frobenius_norm(&(&model.L().row(i) - &L0.row(i)).insert_axis(Axis(0)));
+            assert!(err < 1e-3 * frobenius_norm(&L0.row(i).insert_axis(Axis(0))), "sample {}", i);
+        }
+
+        // the same corruption on the features
+        let hyperparams = rPCAHyperParams::new(m as u64, n as u64)
+            .lambda(lambda)
+            .corruption(Corruption::Columns)
+            .max_n_iterations(5000)
+            .build();
+        let model = rPCA::fit(hyperparams, &X.t());
+        assert_eq!(model.outliers(), corrupted);
+
+        assert!(matches!(
+            rPCAHyperParams::new(n as u64, m as u64).corruption(Corruption::Rows).solver(rPCASolver::Apg).try_build(),
+            Err(Error::InvalidHyperParameter(_))
+        ));
+    }
+}
@@ -0,0 +1,401 @@
+#![allow(non_snake_case, non_camel_case_types)]
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp::max;
+
+/// The SVD routine used by the singular value thresholding step of `rPCA::fit`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SvtSolver {
+    /// Full SVD of the n x m matrix at every iteration.
+    Full,
+    /// Randomized truncated SVD, only computing as many singular triplets as
+    /// the rank predicted from the previous iteration, as in the PROPACK based
+    /// inexact ALM implementations.
+    Partial,
+}
+
+/// The algorithm used by `rPCA::fit` to decompose X into L + S.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum rPCASolver {
+    /// Inexact augmented lagrangian multipliers (ADMM), Candès et al. 2009,
+    /// driven by `mu`, `rho` and `mu_max`. The only solver supporting masks,
+    /// `delta` and `corruption`, it reads every field of `rPCAHyperParams`.
+    Admm,
+    /// Accelerated proximal gradient with continuation on the penalty, Lin et al.,
+    /// "Fast convex optimization algorithms for exact recovery of a corrupted
+    /// low-rank matrix", 2009. Solves the same convex problem as `Admm`, reading
+    /// `lambda`, `svt_solver`, `tolerance` and `max_n_iterations`.
+    Apg,
+    /// Non-convex alternating projections, Netrapalli et al., "Non-convex Robust
+    /// PCA", 2014. Alternates a rank `rank` truncated SVD with a hard thresholding
+    /// of the residual, raising the rank of L one stage at a time. Only reads
+    /// `tolerance` and `max_n_iterations`.
+    AltProj { rank: usize },
+    /// Fast RPCA via gradient descent, Yi et al., "Fast Algorithms for Robust PCA
+    /// via Gradient Descent", 2016. Factorizes L = U V^T with rank `rank` and keeps
+    /// in S the largest residuals, at most a `sparsity` fraction of every row and column.
+    /// Only reads `tolerance` and `max_n_iterations`.
+    GradientDescent { rank: usize, sparsity: f64 },
+}
+
+/// The structure of the corruption captured by S.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Corruption {
+    /// Scattered corrupted entries, penalized by the l1 norm of S.
+    Entries,
+    /// Whole corrupted samples, Outlier Pursuit of Xu, Caramanis & Sanghavi,
+    /// "Robust PCA via Outlier Pursuit", 2010, penalizing the sum of the l2 norms
+    /// of the rows of S. The analysis of the paper takes `lambda = 3 / (7 sqrt(gamma n))`
+    /// for a fraction gamma of outliers among n samples, small problems may need more.
+    Rows,
+    /// Whole corrupted features, the same penalty on the columns of S.
+    Columns,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+// configurations saved before the solver options existed only hold the first four fields
+#[serde(from = "StoredHyperParams")]
+pub struct rPCAHyperParams {
+    /// lambda - regularization parameter, default = 1/sqrt(max(N,M))
+    pub(crate) lambda: f64,
+    /// mu - the augmented lagrangian parameter, default = 10/sqrt(max(N,M))
+    pub(crate) mu : f64,
+    /// tolerance - reconstruction error tolerance, default = 1e-6
+    pub(crate) tolerance : f64,
+    /// max_n_iterations - maximum number of iterations, default = 1000
+    pub(crate) max_n_iterations : u64,
+    /// rho - growth factor of mu after each iteration, default = 1 (fixed mu)
+    pub(crate) rho : f64,
+    /// mu_max - upper bound on mu, default = 1e7*mu
+    pub(crate) mu_max : f64,
+    /// svt_solver - SVD used for singular value thresholding, default = SvtSolver::Full
+    pub(crate) svt_solver : SvtSolver,
+    /// delta - bound on the Frobenius norm of the dense noise, default = 0 (exact PCP)
+    pub(crate) delta : f64,
+    /// solver - algorithm decomposing X, default = rPCASolver::Admm
+    pub(crate) solver : rPCASolver,
+    /// corruption - structure of the sparse part, default = Corruption::Entries
+    pub(crate) corruption : Corruption,
+}
+
+/// The serialized form of `rPCAHyperParams`, filling the fields missing from
+/// older configurations with the defaults of `rPCAHyperParams::new`
+#[derive(Deserialize)]
+struct StoredHyperParams {
+    lambda: f64,
+    mu : f64,
+    tolerance : f64,
+    max_n_iterations : u64,
+    #[serde(default = "default_rho")]
+    rho : f64,
+    #[serde(default)]
+    mu_max : Option<f64>,
+    #[serde(default = "default_svt_solver")]
+    svt_solver : SvtSolver,
+    #[serde(default)]
+    delta : f64,
+    #[serde(default = "default_solver")]
+    solver : rPCASolver,
+    #[serde(default = "default_corruption")]
+    corruption : Corruption,
+}
+
+fn default_rho() -> f64 { 1.0 }
+
+fn default_svt_solver() -> SvtSolver { SvtSolver::Full }
+
+fn default_solver() -> rPCASolver { rPCASolver::Admm }
+
+fn default_corruption() -> Corruption { Corruption::Entries }
+
+impl From<StoredHyperParams> for rPCAHyperParams {
+    fn from(stored: StoredHyperParams) -> Self {
+        rPCAHyperParams {
+            lambda: stored.lambda,
+            mu: stored.mu,
+            tolerance: stored.tolerance,
+            max_n_iterations: stored.max_n_iterations,
+            rho: stored.rho,
+            mu_max: stored.mu_max.unwrap_or(1.0e7 * stored.mu),
+            svt_solver: stored.svt_solver,
+            delta: stored.delta,
+            solver: stored.solver,
+            corruption: stored.corruption,
+        }
+    }
+}
+
+/// An helper struct used to construct a set of [valid hyperparameters]
+pub struct rPCAHyperParamsBuilder {
+    lambda: f64,
+    mu : Option<f64>,
+    default_mu : f64,
+    tolerance : f64,
+    max_n_iterations: u64,
+    rho : f64,
+    mu_max : Option<f64>,
+    svt_solver : SvtSolver,
+    delta : f64,
+    solver : rPCASolver,
+    corruption : Corruption,
+}
+
+impl rPCAHyperParamsBuilder {
+
+    /// Set the value of `lambda`.
+    ///
+    /// Lambda is simply a regularization parameter used during the
+    /// ADMM loop
+    pub fn lambda(mut self, lambda: f64) -> Self {
+        self.lambda = lambda;
+        self
+    }
+
+    /// Set the value of `mu`.
+    ///
+    /// Mu is the augmented lagrangian multiplier
+    pub fn mu(mut self, mu: f64) -> Self {
+        self.mu = Some(mu);
+        self
+    }
+
+    /// Set the value of `tolerance`.
+    ///
+    /// The training is considered complete once the relative residual
+    /// ||X - L - S||_F / ||X||_F is lower than the tolerance, or the relative
+    /// norm of the subgradient for `rPCASolver::Apg`
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Set the value of `max_n_iterations`.
+    ///
+    /// We exit the ADMM (alternating direction method of multipliers) loop once
+    /// the max_n_iterations have been reached or the error is less
+    /// than the tolerance
+    pub fn max_n_iterations(mut self, max_n_iterations: u64) -> Self {
+        self.max_n_iterations = max_n_iterations;
+        self
+    }
+
+    /// Set the value of `rho`.
+    ///
+    /// After every iteration mu is multiplied by rho, up to `mu_max`, which is the
+    /// inexact augmented lagrangian multiplier schedule. A rho of 1 keeps mu fixed.
+    /// The growing schedule expects a small starting `mu`, such as 1.25/||X||_2
+    pub fn rho(mut self, rho: f64) -> Self {
+        self.rho = rho;
+        self
+    }
+
+    /// Set the value of `mu_max`.
+    ///
+    /// The upper bound of the growing mu, defaults to 1e7 times the initial mu
+    pub fn mu_max(mut self, mu_max: f64) -> Self {
+        self.mu_max = Some(mu_max);
+        self
+    }
+
+    /// Keep mu constant through the ADMM loop, as in the exact formulation
+    pub fn fixed_mu(self) -> Self {
+        self.rho(1.0)
+    }
+
+    /// Set the value of `svt_solver`.
+    ///
+    /// `SvtSolver::Partial` avoids the full SVD of every iteration, which
+    /// dominates the runtime on large matrices with a low-rank component
+    pub fn svt_solver(mut self, svt_solver: SvtSolver) -> Self {
+        self.svt_solver = svt_solver;
+        self
+    }
+
+    /// Set the value of `delta`.
+    ///
+    /// A positive delta switches to stable principal component pursuit, Zhou et al.,
+    /// "Stable Principal Component Pursuit", 2010, which decomposes X = L + S + N
+    /// with a dense noise N of Frobenius norm at most delta. For i.i.d. noise of
+    /// standard deviation sigma the paper suggests delta = sqrt(n*m + sqrt(8*n*m)) * sigma
+    pub fn delta(mut self, delta: f64) -> Self {
+        self.delta = delta;
+        self
+    }
+
+    /// Set the value of `solver`.
+    ///
+    /// `lambda` weighs the sparse part for `Admm` and `Apg`, the non-convex
+    /// solvers are driven by their target rank instead
+    pub fn solver(mut self, solver: rPCASolver) -> Self {
+        self.solver = solver;
+        self
+    }
+
+    /// Set the value of `corruption`.
+    ///
+    /// `Corruption::Rows` flags whole samples as outliers rather than entries,
+    /// they are listed by `rPCA::outliers`
+    pub fn corruption(mut self, corruption: Corruption) -> Self {
+        self.corruption = corruption;
+        self
+    }
+
+    /// Return an instance of `rPCAHyperParams` after
+    /// having performed validation checks on all the specified hyperparamters.
+    ///
+    /// **Panics** if any of the validation checks fails.
+    pub fn build(self) -> rPCAHyperParams {
+        match self.try_build() {
+            Ok(hyperparameters) => hyperparameters,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Return an instance of `rPCAHyperParams` after
+    /// having performed validation checks on all the specified hyperparamters,
+    /// or the first check that failed.
+    pub fn try_build(self) -> Result<rPCAHyperParams> {
+        if self.solver != rPCASolver::Admm && (self.mu.is_some() || self.mu_max.is_some()) {
+            return Err(Error::InvalidHyperParameter("`mu` and `mu_max` are only supported by `rPCASolver::Admm`!".to_string()));
+        }
+        let mu = self.mu.unwrap_or(self.default_mu);
+        let mu_max = self.mu_max.unwrap_or(1.0e7 * mu);
+        rPCAHyperParams::build(self.lambda, mu, self.tolerance, self.max_n_iterations, self.rho, mu_max, self.svt_solver, self.delta, self.solver, self.corruption)
+    }
+}
+
+impl rPCAHyperParams {
+    /// `new` lets us configure our training algorithm parameters:
+    /// * `lambda` weighs the sparse part S against the nuclear norm of L;
+    /// * `mu` is the penalty of the augmented lagrangian;
+    /// * the training is considered complete once the Frobenius norm of the
+    ///   residual X - L - S, relative to that of X, is lower than `tolerance`;
+    /// * we exit the training loop when the number of training iterations
+    ///   exceeds `max_n_iterations` even if the `tolerance` convergence
+    ///   condition has not been met.
+    ///
+    /// The shape (`_n`, `_m`) of the data is mandatory, it sets the defaults of
+    /// `lambda` and `mu`.
+    ///
+    /// Defaults are provided if optional parameters are not specified:
+    /// * `lambda = 1 / sqrt(max(_n, _m))`;
+    /// * `mu = 10 / sqrt(max(_n, _m))`;
+    /// * `tolerance = 1e-6`;
+    /// * `max_n_iterations = 1000`;
+    /// * `rho = 1`;
+    /// * `mu_max = 1e7 * mu`;
+    /// * `svt_solver = SvtSolver::Full`;
+    /// * `delta = 0`;
+    /// * `solver = rPCASolver::Admm`;
+    /// * `corruption = Corruption::Entries`.
+    pub fn new(_n : u64, _m : u64) -> rPCAHyperParamsBuilder {
+        rPCAHyperParamsBuilder {
+            lambda : 1.0 / (max(_n, _m) as f64).sqrt() as f64,
+            mu : None,
+            default_mu : 10.0 / (max(_n, _m) as f64).sqrt() as f64,
+            tolerance : 1.0e-6,
+            max_n_iterations : 1000,
+            rho : default_rho(),
+            mu_max : None,
+            svt_solver : default_svt_solver(),
+            delta : 0.0,
+            solver : default_solver(),
+            corruption : default_corruption(),
+        }
+    }
+
+    /// The weight of the sparse part S against the nuclear norm of L.
+    pub fn lambda(&self) -> f64 { self.lambda }
+
+    /// The initial penalty of the augmented lagrangian.
+    pub fn mu(&self) -> f64 { self.mu }
+
+    /// The training is considered complete once the Frobenius norm of the
+    /// residual, relative to that of X, is lower than `tolerance`.
+    pub fn tolerance(&self) -> f64 {
+        self.tolerance
+    }
+
+    /// We exit the training loop when the number of training iterations
+    /// exceeds `max_n_iterations` even if the `tolerance` convergence
+    /// condition has not been met.
+    pub fn max_n_iterations(&self) -> u64 {
+        self.max_n_iterations
+    }
+
+    /// The factor mu grows by after each iteration, 1 for a fixed mu.
+    pub fn rho(&self) -> f64 { self.rho }
+
+    /// The upper bound on mu.
+    pub fn mu_max(&self) -> f64 { self.mu_max }
+
+    /// The SVD routine used for singular value thresholding.
+    pub fn svt_solver(&self) -> SvtSolver { self.svt_solver }
+
+    /// The bound on the Frobenius norm of the dense noise, 0 for exact PCP.
+    pub fn delta(&self) -> f64 { self.delta }
+
+    /// The algorithm decomposing X.
+    pub fn solver(&self) -> rPCASolver { self.solver }
+
+    /// The structure of the corruption captured by S.
+    pub fn corruption(&self) -> Corruption { self.corruption }
+
+    #[allow(clippy::too_many_arguments)]
+    fn build(lambda : f64, mu : f64, tolerance : f64, max_n_iterations : u64, rho : f64, mu_max : f64, svt_solver : SvtSolver, delta : f64, solver : rPCASolver, corruption : Corruption) -> Result<Self> {
+        if max_n_iterations == 0 {
+            return Err(Error::InvalidHyperParameter("`max_n_iterations` cannot be 0!".to_string()));
+        }
+        if tolerance.is_nan() || tolerance <= 0. {
+            return Err(Error::InvalidHyperParameter("`tolerance` must be greater than 0!".to_string()));
+        }
+        if !mu.is_finite() || mu <= 0.0 {
+            return Err(Error::InvalidHyperParameter("`mu` must be greater than 0!".to_string()));
+        }
+        if !lambda.is_finite() || lambda <= 0.0 {
+            return Err(Error::InvalidHyperParameter("`lambda` must be greater than 0!".to_string()));
+        }
+        if rho.is_nan() || rho < 1.0 {
+            return Err(Error::InvalidHyperParameter("`rho` must be at least 1!".to_string()));
+        }
+        if mu_max.is_nan() || mu_max < mu {
+            return Err(Error::InvalidHyperParameter("`mu_max` cannot be smaller than `mu`!".to_string()));
+        }
+        if !delta.is_finite() || delta < 0.0 {
+            return Err(Error::InvalidHyperParameter("`delta` must be finite and non-negative!".to_string()));
+        }
+        if rho != 1.0 && solver != rPCASolver::Admm {
+            return Err(Error::InvalidHyperParameter("`rho` is only supported by `rPCASolver::Admm`!".to_string()));
+        }
+        if svt_solver != SvtSolver::Full && !matches!(solver, rPCASolver::Admm | rPCASolver::Apg) {
+            return Err(Error::InvalidHyperParameter("`svt_solver` is only supported by `rPCASolver::Admm` and `rPCASolver::Apg`!".to_string()));
+        }
+        if delta > 0.0 && solver != rPCASolver::Admm {
+            return Err(Error::InvalidHyperParameter("`delta` is only supported by `rPCASolver::Admm`!".to_string()));
+        }
+        if corruption != Corruption::Entries && solver != rPCASolver::Admm {
+            return Err(Error::InvalidHyperParameter("`Corruption::Rows` and `Corruption::Columns` are only supported by `rPCASolver::Admm`!".to_string()));
+        }
+        match solver {
+            rPCASolver::AltProj { rank } | rPCASolver::GradientDescent { rank, .. } if rank == 0 => {
+                return Err(Error::InvalidHyperParameter("`rank` cannot be 0!".to_string()));
+            }
+            rPCASolver::GradientDescent { sparsity, .. } if !(sparsity > 0.0 && sparsity < 1.0) => {
+                return Err(Error::InvalidHyperParameter("`sparsity` must be in (0, 1)!".to_string()));
+            }
+            _ => {}
+        }
+        Ok(rPCAHyperParams {
+            lambda,
+            mu,
+            tolerance,
+            max_n_iterations,
+            rho,
+            mu_max,
+            svt_solver,
+            delta,
+            solver,
+            corruption,
+        })
+    }
+}
@@ -0,0 +1,8 @@
+mod algorithm;
+mod hyperparameters;
+mod report;
+mod solvers;
+
+pub use self::algorithm::*;
+pub use self::hyperparameters::*;
+pub use self::report::*;
@@ -0,0 +1,42 @@
+#![allow(non_snake_case, non_camel_case_types)]
+use serde::{Deserialize, Serialize};
+
+/// Convergence diagnostics of an `rPCA` fit
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FitReport {
+    /// number of iterations that were run
+    pub(crate) n_iterations: u64,
+    /// whether the stopping criterion dropped below `tolerance`, the relative
+    /// residual or, for `rPCASolver::Apg`, the relative norm of the subgradient
+    pub(crate) converged: bool,
+    /// ||X - L - S||_F / ||X||_F after each iteration, for every solver
+    pub(crate) residuals: Vec<f64>,
+    /// number of non-zero singular values of `L`
+    pub(crate) rank: usize,
+    /// number of non-zero entries of `S`
+    pub(crate) nnz: usize,
+}
+
+impl FitReport {
+    /// The number of iterations run before stopping.
+    pub fn n_iterations(&self) -> u64 { self.n_iterations }
+
+    /// Whether the stopping criterion met the `tolerance` before
+    /// `max_n_iterations` was reached. It is the relative residual, except for
+    /// `rPCASolver::Apg` which stops on the relative norm of its subgradient.
+    pub fn converged(&self) -> bool { self.converged }
+
+    /// The relative residual ||X - L - S||_F / ||X||_F of the last iteration.
+    pub fn residual(&self) -> f64 {
+        self.residuals.last().cloned().unwrap_or(0.0)
+    }
+
+    /// The relative residual of every iteration, in order.
+    pub fn residuals(&self) -> &[f64] { &self.residuals }
+
+    /// The rank of the low-rank component `L`.
+    pub fn rank(&self) -> usize { self.rank }
+
+    /// The number of non-zero entries of the sparse component `S`.
+    pub fn nnz(&self) -> usize { self.nnz }
+}
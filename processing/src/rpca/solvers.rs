@@ -0,0 +1,292 @@
+#![allow(non_snake_case, non_camel_case_types)]
+use crate::error::Result;
+use crate::float::Float;
+use crate::rpca::algorithm::{frobenius_norm, svt, svt_partial, So};
+use crate::rpca::hyperparameters::{rPCAHyperParams, SvtSolver};
+use crate::rpca::report::FitReport;
+use ndarray::{ArrayBase, Array1, Array2, Axis, Data, Ix2, Zip, s};
+use ndarray_linalg::SVD;
+use ndarray_rand::rand::{SeedableRng, rngs::StdRng};
+
+/// Accelerated proximal gradient with continuation, Lin et al. 2009, algorithm 2.
+///
+/// Minimizes mu ||L||_* + lambda mu ||S||_1 + 1/2 ||X - L - S||_F^2 with Nesterov
+/// momentum, shrinking mu geometrically from 0.99 ||X||_2 down to 1e-9 ||X||_2,
+/// until the relative norm of the subgradient is lower than `tolerance`.
+pub(crate) fn apg<A: Float>(
+    hyperparameters: &rPCAHyperParams,
+    X: &ArrayBase<impl Data<Elem = A>, Ix2>,
+) -> Result<(Array2<A>, Array2<A>, FitReport)> {
+    let (_n, _m) = X.dim();
+    let normX = frobenius_norm(X);
+    let lambda = A::real(hyperparameters.lambda);
+    let (_, sigma, _) = X.svd(false, false)?;
+    let mut mu = A::real(0.99) * sigma[0];
+    let mu_min = A::real(1e-9) * sigma[0];
+    let eta = A::real(0.9);
+    let half = A::real(0.5);
+
+    let mut L = Array2::zeros(X.dim()) as Array2<A>;
+    let mut S = Array2::zeros(X.dim()) as Array2<A>;
+    let (mut L_prev, mut S_prev) = (L.clone(), S.clone());
+    let (mut t, mut t_prev) = (A::one(), A::one());
+    let mut residuals = Vec::new();
+    let mut rank = 0;
+    let mut converged = false;
+    let max_rank = _n.min(_m);
+    let mut sv = max_rank.min(10);
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..hyperparameters.max_n_iterations {
+        let momentum = (t_prev - A::one()) / t;
+        let YL = &L + &((&L - &L_prev) * momentum);
+        let YS = &S + &((&S - &S_prev) * momentum);
+        // half a gradient step on the smooth term, shared by both blocks
+        let gradient = (&YL + &YS - X) * half;
+
+        let (newL, newRank) = match hyperparameters.svt_solver {
+            SvtSolver::Full => svt(mu * half, &(&YL - &gradient))?,
+            SvtSolver::Partial => svt_partial(mu * half, &(&YL - &gradient), sv, &mut rng)?,
+        };
+        let newS = So(lambda * mu * half, &(&YS - &gradient));
+        rank = newRank;
+        sv = if rank < sv {
+            max_rank.min(rank + 1)
+        } else {
+            max_rank.min(rank + (max_rank / 20).max(1))
+        };
+
+        // the subgradient of the objective at the new point, section 4.1
+        let step = &newL + &newS - &YL - &YS;
+        let gradient_L = (&YL - &newL) * A::real(2.0) + &step;
+        let gradient_S = (&YS - &newS) * A::real(2.0) + &step;
+        let scale = (frobenius_norm(&newL).powi(2) + frobenius_norm(&newS).powi(2)).sqrt().max(A::one());
+        let err = (frobenius_norm(&gradient_L).powi(2) + frobenius_norm(&gradient_S).powi(2)).sqrt()
+            / (A::real(2.0) * scale);
+
+        L_prev = std::mem::replace(&mut L, newL);
+        S_prev = std::mem::replace(&mut S, newS);
+        t_prev = t;
+        t = (A::one() + (A::real(4.0) * t * t + A::one()).sqrt()) * half;
+        mu = (mu * eta).max(mu_min);
+
+        // the subgradient stops the iterations, L + S only approaches X, but the
+        // report holds the same relative residual as the other solvers
+        let residual = if normX > A::zero() {
+            (frobenius_norm(&(X - &L - &S)) / normX).to_f64().expect("a float converts to f64")
+        } else {
+            0.0
+        };
+        residuals.push(residual);
+        if err.to_f64().expect("a float converts to f64") < hyperparameters.tolerance {
+            converged = true;
+            break;
+        }
+    }
+    let report = report(residuals, converged, rank, &S);
+    Ok((L, S, report))
+}
+
+/// Non-convex alternating projections, Netrapalli et al. 2014, algorithm 1.
+///
+/// Stage k alternates the projection of X - S on the matrices of rank k with the
+/// hard thresholding of X - L at a level decaying towards beta sigma_{k+1}(X - S),
+/// and moves to rank k + 1 once the level no longer decreases.
+pub(crate) fn alt_proj<A: Float>(
+    hyperparameters: &rPCAHyperParams,
+    X: &ArrayBase<impl Data<Elem = A>, Ix2>,
+    target_rank: usize,
+) -> Result<(Array2<A>, Array2<A>, FitReport)> {
+    let (_n, _m) = X.dim();
+    let normX = frobenius_norm(X);
+    // the level of the paper, 4 mu^2 r / sqrt(n m), for an incoherence mu of 1
+    let beta = A::real(4.0 * target_rank as f64 / ((_n * _m) as f64).sqrt());
+    let (_, sigma, _) = X.svd(false, false)?;
+    let mut S = hard_threshold(X, beta * sigma[0]);
+    let mut L = Array2::zeros(X.dim()) as Array2<A>;
+
+    let mut residuals = Vec::new();
+    let mut converged = false;
+    let (mut k, mut t) = (1, 0);
+    for _ in 0..hyperparameters.max_n_iterations {
+        let (newL, sigma) = truncated_svd(&(X - &S), k)?;
+        L = newL;
+        let next = if k < sigma.len() { sigma[k] } else { A::zero() };
+        let decay = A::real(0.5f64.powi(t));
+        let zeta = beta * (next + decay * sigma[k - 1]);
+        S = hard_threshold(&(X - &L), zeta);
+
+        let err = if normX > A::zero() {
+            (frobenius_norm(&(X - &L - &S)) / normX).to_f64().expect("a float converts to f64")
+        } else {
+            0.0
+        };
+        residuals.push(err);
+        if err < hyperparameters.tolerance {
+            converged = true;
+            break;
+        }
+
+        t += 1;
+        // the level is dominated by sigma_{k+1}, add a rank if there is one to find
+        let tiny = A::real(hyperparameters.tolerance) * sigma[0];
+        if k < target_rank && decay * sigma[k - 1] <= next && next > tiny {
+            k += 1;
+            t = 0;
+        }
+    }
+    let report = report(residuals, converged, k, &S);
+    Ok((L, S, report))
+}
+
+/// Fast RPCA via gradient descent, Yi et al. 2016, algorithm 1.
+///
+/// L = U V^T is initialized from the truncated SVD of X minus its largest entries,
+/// then every iteration keeps in S the largest residuals of each row and column and
+/// takes a gradient step on the factors, balanced by the (U^T U - V^T V) regularizer.
+pub(crate) fn gradient_descent<A: Float>(
+    hyperparameters: &rPCAHyperParams,
+    X: &ArrayBase<impl Data<Elem = A>, Ix2>,
+    target_rank: usize,
+    sparsity: f64,
+) -> Result<(Array2<A>, Array2<A>, FitReport)> {
+    gradient_descent_with_step(hyperparameters, X, target_rank, sparsity, 0.5)
+}
+
+/// `gradient_descent` with a step of `step_scale / sigma_1(X)`. Steps too large
+/// make the factors blow up, the descent then stops unconverged at the last
+/// iterate whose residual is finite.
+fn gradient_descent_with_step<A: Float>(
+    hyperparameters: &rPCAHyperParams,
+    X: &ArrayBase<impl Data<Elem = A>, Ix2>,
+    target_rank: usize,
+    sparsity: f64,
+    step_scale: f64,
+) -> Result<(Array2<A>, Array2<A>, FitReport)> {
+    let normX = frobenius_norm(X);
+    let mut S = sparsify(X, sparsity);
+
+    // balanced factors of the rank r approximation
+    let (u, sigma, vt) = (X - &S).svd(true, true)?;
+    let u = u.expect("U was requested from the SVD");
+    let vt = vt.expect("V^T was requested from the SVD");
+    let root = sigma.slice(s![..target_rank]).mapv(|x| x.sqrt());
+    let mut U = &u.slice(s![.., ..target_rank]) * &root;
+    let mut V = &vt.slice(s![..target_rank, ..]).t() * &root;
+    let step = A::real(step_scale) / sigma[0].max(A::min_positive_value());
+    let half = A::real(0.5);
+
+    let mut L = U.dot(&V.t());
+    let mut residuals = Vec::new();
+    let mut converged = false;
+    for _ in 0..hyperparameters.max_n_iterations {
+        S = sparsify(&(X - &L), sparsity);
+        let residual = &L + &S - X;
+        let balance = U.t().dot(&U) - V.t().dot(&V);
+        let gradient_U = residual.dot(&V) + U.dot(&balance) * half;
+        let gradient_V = residual.t().dot(&U) - V.dot(&balance) * half;
+        let newU = &U - &(gradient_U * step);
+        let newV = &V - &(gradient_V * step);
+        let newL = newU.dot(&newV.t());
+
+        let err = if normX > A::zero() {
+            (frobenius_norm(&(X - &newL - &S)) / normX).to_f64().expect("a float converts to f64")
+        } else {
+            0.0
+        };
+        if !err.is_finite() {
+            // diverged, keep the last finite iterate
+            break;
+        }
+        U = newU;
+        V = newV;
+        L = newL;
+        residuals.push(err);
+        if err < hyperparameters.tolerance {
+            converged = true;
+            break;
+        }
+    }
+    let report = report(residuals, converged, target_rank, &S);
+    Ok((L, S, report))
+}
+
+fn report<A: Float>(residuals: Vec<f64>, converged: bool, rank: usize, S: &Array2<A>) -> FitReport {
+    FitReport {
+        n_iterations: residuals.len() as u64,
+        converged,
+        residuals,
+        rank,
+        nnz: S.iter().filter(|&&x| x != A::zero()).count(),
+    }
+}
+
+/// The best rank `k` approximation of `X`, and all the singular values of `X`
+fn truncated_svd<A: Float>(
+    X: &ArrayBase<impl Data<Elem = A>, Ix2>,
+    k: usize,
+) -> Result<(Array2<A>, Array1<A>)> {
+    let (u, sigma, vt) = X.svd(true, true)?;
+    let u = u.expect("U was requested from the SVD");
+    let vt = vt.expect("V^T was requested from the SVD");
+    let L = (&u.slice(s![.., ..k]) * &sigma.slice(s![..k])).dot(&vt.slice(s![..k, ..]));
+    Ok((L, sigma))
+}
+
+/// Keep the entries of `X` whose magnitude is larger than `zeta`
+fn hard_threshold<A: Float>(X: &ArrayBase<impl Data<Elem = A>, Ix2>, zeta: A) -> Array2<A> {
+    X.mapv(|x| if x.abs() > zeta { x } else { A::zero() })
+}
+
+/// Keep the entries of `X` that are among the `fraction` largest in magnitude of
+/// both their row and their column, the T_alpha operator of Yi et al.
+fn sparsify<A: Float>(X: &ArrayBase<impl Data<Elem = A>, Ix2>, fraction: f64) -> Array2<A> {
+    let (_n, _m) = X.dim();
+    let kth_largest = |lane: ndarray::ArrayView1<A>, k: usize| {
+        let mut magnitudes: Vec<A> = lane.iter().map(|x| x.abs()).collect();
+        // a NaN, which finite inputs never produce, ranks below every magnitude
+        magnitudes.sort_by(|a, b| b.partial_cmp(a).unwrap_or_else(|| a.is_nan().cmp(&b.is_nan())));
+        magnitudes[k - 1]
+    };
+    let k_row = ((fraction * _m as f64).ceil() as usize).max(1).min(_m);
+    let k_column = ((fraction * _n as f64).ceil() as usize).max(1).min(_n);
+    let row_levels = X.map_axis(Axis(1), |row| kth_largest(row, k_row));
+    let column_levels = X.map_axis(Axis(0), |column| kth_largest(column, k_column));
+
+    let mut out = Array2::zeros(X.dim());
+    Zip::indexed(&mut out).and(X).apply(|(i, j), o, &x| {
+        if x != A::zero() && x.abs() >= row_levels[i] && x.abs() >= column_levels[j] {
+            *o = x;
+        }
+    });
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{Array, array};
+    use ndarray_rand::RandomExt;
+    use ndarray_rand::rand_distr::Uniform;
+
+    #[test]
+    fn test_gradient_descent_divergence() {
+        let mut rng = StdRng::seed_from_u64(31);
+        let X: Array2<f64> = Array::random_using((40, 30), Uniform::new(-1., 1.), &mut rng);
+        let hyperparameters = rPCAHyperParams::new(40, 30).max_n_iterations(500).build();
+
+        // a step a hundred times too large blows the factors up
+        let (L, S, report) = gradient_descent_with_step(&hyperparameters, &X, 3, 0.1, 50.0).unwrap();
+        assert!(!report.converged());
+        assert!(report.n_iterations() < 500);
+        assert!(report.residuals().iter().all(|x| x.is_finite()));
+        assert!(L.iter().chain(S.iter()).all(|x| x.is_finite()));
+    }
+
+    #[test]
+    fn test_sparsify_nan() {
+        let X = array![[1.0, f64::NAN, -3.0], [0.5, 2.0, 0.0]];
+        // the NaN is never kept and does not hide the largest entries
+        let S = sparsify(&X, 0.3);
+        assert_eq!(S, array![[0.0, 0.0, -3.0], [0.0, 2.0, 0.0]]);
+    }
+}
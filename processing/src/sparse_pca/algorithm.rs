@@ -0,0 +1,345 @@
+#![allow(non_snake_case, non_camel_case_types)]
+use ndarray::{ArrayBase, Array2, Array1, Axis, Data, Ix2, s};
+use ndarray_linalg::{Eigh, Inverse, SVD, UPLO};
+use crate::error::{check_input, Error, Result};
+use crate::rpca::{frobenius_norm, So};
+use crate::sparse_pca::hyperparameters::SparsePCAHyperParams;
+
+/// Sparse PCA through the l1 penalized dictionary formulation of Mairal et al.,
+/// "Online dictionary learning for sparse coding", 2009:
+///
+/// min 1/2 ||X - U V||_F^2 + alpha ||V||_1 subject to ||U_k||_2 <= 1,
+///
+/// where the rows of V are the sparse components. The problem is solved by
+/// alternating a proximal gradient step on V with a block coordinate descent on U.
+pub struct SparsePCA {
+    /// sparse components as rows, normalized to unit norm, shape (n_components, n_features)
+    components: Array2<f64>,
+    mean : Array1<f64>,
+    n_iterations: u64,
+    converged: bool,
+    hyperparameters: SparsePCAHyperParams,
+}
+
+impl SparsePCA {
+
+    /// Fit the model on `x`, of shape (n_samples, n_features).
+    ///
+    /// **Panics** if `try_fit` returns an error.
+    pub fn fit (
+        hyperparameters: SparsePCAHyperParams,
+        x : &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> Self {
+        match SparsePCA::try_fit(hyperparameters, x) {
+            Ok(model) => model,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Fit the model on `x`, of shape (n_samples, n_features).
+    ///
+    /// Fails if `x` has fewer than two samples or non-finite values, if
+    /// `n_components` is larger than min(n_samples, n_features) or if a LAPACK
+    /// routine fails.
+    pub fn try_fit (
+        hyperparameters: SparsePCAHyperParams,
+        x : &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> Result<Self> {
+        check_input(x, 2)?;
+        let (_n, _m) = x.dim();
+        let k = hyperparameters.n_components;
+        if k > _n.min(_m) {
+            return Err(Error::TooManyComponents { requested: k, max: _n.min(_m) });
+        }
+
+        let mean = x.mean_axis(Axis(0)).ok_or(Error::EmptyInput)?;
+        let b = x - &mean;
+
+        // start from the leading singular triplets, U has orthonormal columns
+        let (u, sigma, vt) = b.svd(true, true)?;
+        let u = u.expect("U was requested from the SVD");
+        let vt = vt.expect("V^T was requested from the SVD");
+        let mut U = u.slice(s![.., ..k]).to_owned();
+        let mut V = vt.slice(s![..k, ..]).to_owned() * sigma.slice(s![..k]).insert_axis(Axis(1));
+
+        let alpha = hyperparameters.alpha;
+        let objective = |U: &Array2<f64>, V: &Array2<f64>| {
+            0.5 * frobenius_norm(&(&b - &U.dot(V))).powi(2) + alpha * V.map(|x| x.abs()).sum()
+        };
+        let mut current = objective(&U, &V);
+        let mut n_iterations = 0;
+        let mut converged = false;
+        for _ in 0..hyperparameters.max_n_iterations {
+            n_iterations += 1;
+
+            // proximal gradient step on the components, with the inverse of the
+            // lipschitz constant of the gradient as step size
+            let (w, _) = U.t().dot(&U).eigh(UPLO::Upper)?;
+            let lipschitz = w[k - 1].max(f64::EPSILON);
+            let gradient = U.t().dot(&(U.dot(&V) - &b));
+            V = So(alpha / lipschitz, &(&V - &(gradient / lipschitz)));
+
+            // block coordinate descent on the codes, projected on the unit ball
+            let mut residual = &b - &U.dot(&V);
+            for j in 0..k {
+                let norm = V.row(j).map(|x| x.powi(2)).sum();
+                if norm == 0.0 {
+                    continue;
+                }
+                let row = V.row(j).insert_axis(Axis(0));
+                let mut column = U.column(j).to_owned();
+                residual = residual + column.view().insert_axis(Axis(1)).dot(&row);
+                column = residual.dot(&V.row(j)) / norm;
+                let length = column.map(|x| x.powi(2)).sum().sqrt();
+                if length > 1.0 {
+                    column /= length;
+                }
+                residual = residual - column.view().insert_axis(Axis(1)).dot(&row);
+                U.column_mut(j).assign(&column);
+            }
+
+            let next = objective(&U, &V);
+            let decrease = current - next;
+            current = next;
+            if decrease.abs() <= hyperparameters.tolerance * current.max(f64::MIN_POSITIVE) {
+                converged = true;
+                break;
+            }
+        }
+
+        // unit norm components, the scale is carried by the codes
+        for mut row in V.outer_iter_mut() {
+            let length = row.map(|x| x.powi(2)).sum().sqrt();
+            if length > 0.0 {
+                row /= length;
+            }
+        }
+
+        Ok(Self {
+            components: V,
+            mean,
+            n_iterations,
+            converged,
+            hyperparameters,
+        })
+    }
+
+    /// Project `x` onto the sparse components, after centering it with the mean
+    /// seen during `fit`. As the components are not orthogonal, the projection is
+    /// a ridge regression penalized by `ridge_alpha`. Returns an array of shape
+    /// (n_samples, n_components).
+    ///
+    /// **Panics** if `try_transform` returns an error.
+    pub fn transform (
+        &self,
+        x : &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> Array2<f64> {
+        match self.try_transform(x) {
+            Ok(z) => z,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Project `x` onto the sparse components, after centering it with the mean
+    /// seen during `fit`. Returns an array of shape (n_samples, n_components).
+    ///
+    /// Fails if `x` does not have the number of features seen during `fit`, or
+    /// with `Error::LinearSolve` if the regularized gram matrix of the components
+    /// cannot be inverted.
+    pub fn try_transform (
+        &self,
+        x : &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> Result<Array2<f64>> {
+        let (k, n_features) = self.components.dim();
+        if x.ncols() != n_features {
+            return Err(Error::FeatureMismatch { expected: n_features, found: x.ncols() });
+        }
+        // positive definite as `ridge_alpha` is positive, even with zero components
+        let gram = self.components.dot(&self.components.t())
+            + Array2::<f64>::eye(k) * self.hyperparameters.ridge_alpha;
+        let inverse = gram.inv().map_err(Error::LinearSolve)?;
+        Ok((x - &self.mean).dot(&self.components.t()).dot(&inverse))
+    }
+
+    /// Map codes produced by `transform` back to the original feature space.
+    ///
+    /// **Panics** if `try_inverse_transform` returns an error.
+    pub fn inverse_transform (
+        &self,
+        z : &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> Array2<f64> {
+        match self.try_inverse_transform(z) {
+            Ok(x) => x,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Map codes produced by `transform` back to the original feature space.
+    ///
+    /// Fails if `z` does not have one column per component.
+    pub fn try_inverse_transform (
+        &self,
+        z : &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> Result<Array2<f64>> {
+        let n_components = self.components.nrows();
+        if z.ncols() != n_components {
+            return Err(Error::ComponentMismatch { expected: n_components, found: z.ncols() });
+        }
+        Ok(z.dot(&self.components) + &self.mean)
+    }
+
+    pub fn mean(&self) -> &Array1<f64> {
+        &self.mean
+    }
+
+    /// The sparse components as unit norm rows, shape (n_components, n_features).
+    /// A component whose loadings are all zero stays zero.
+    pub fn components(&self) -> &Array2<f64> {
+        &self.components
+    }
+
+    /// Fraction of exactly zero loadings in each component.
+    pub fn sparsity(&self) -> Array1<f64> {
+        let m = self.components.ncols() as f64;
+        self.components.map_axis(Axis(1), |row| row.iter().filter(|&&x| x == 0.0).count() as f64 / m)
+    }
+
+    /// The number of iterations run by `fit`.
+    pub fn n_iterations(&self) -> u64 {
+        self.n_iterations
+    }
+
+    /// Whether `fit` met the tolerance before `max_n_iterations`.
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    /// The hyperparameters used during `fit`.
+    pub fn hyperparameters(&self) -> &SparsePCAHyperParams {
+        &self.hyperparameters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pca::{Components, PCA, PCAHyperParams};
+    use ndarray::{Array, Array2};
+    use ndarray_rand::RandomExt;
+    use ndarray_rand::rand::{SeedableRng, rngs::StdRng};
+    use ndarray_rand::rand_distr::Uniform;
+
+    /// Two latent factors, each driving its own block of five features,
+    /// followed by five pure noise features
+    fn block_factors(n: usize, seed: u64) -> Array2<f64> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let factors: Array2<f64> = Array::random_using((n, 2), Uniform::new(-10., 10.), &mut rng);
+        let mut X: Array2<f64> = Array::random_using((n, 15), Uniform::new(-0.5, 0.5), &mut rng);
+        for j in 0..5 {
+            let mut first = X.column_mut(j);
+            first += &factors.column(0);
+            let mut second = X.column_mut(5 + j);
+            second += &factors.column(1);
+        }
+        X
+    }
+
+    #[test]
+    fn test_SparsePCA_finds_blocks() {
+        let X = block_factors(200, 11);
+        let model = SparsePCA::fit(SparsePCAHyperParams::new(2).alpha(50.0).build(), &X);
+        assert!(model.converged());
+        assert_eq!(model.components().dim(), (2, 15));
+
+        // every component loads on a single block
+        let mut blocks = Vec::new();
+        for row in model.components().outer_iter() {
+            let first = row.slice(s![..5]).iter().all(|&x| x != 0.0);
+            let second = row.slice(s![5..10]).iter().all(|&x| x != 0.0);
+            assert!(first ^ second);
+            assert!(row.slice(s![10..]).iter().all(|&x| x == 0.0));
+            blocks.push(first);
+        }
+        assert_ne!(blocks[0], blocks[1]);
+        assert!(model.sparsity().iter().all(|&s| (s - 10.0 / 15.0).abs() < 1e-12));
+
+        // while PCA spreads the loadings over every feature
+        let pca = PCA::fit(PCAHyperParams::new(Components::Count(2)).build(), &X);
+        assert!(pca.components().iter().all(|&x| x != 0.0));
+
+        // and the codes still reconstruct the data up to the noise
+        let Z = model.transform(&X);
+        assert_eq!(Z.dim(), (200, 2));
+        let reconstruction = model.inverse_transform(&Z);
+        assert!((reconstruction - &X).iter().all(|x| x.abs() < 2.0));
+    }
+
+    #[test]
+    fn test_SparsePCA_alpha() {
+        let mut rng = StdRng::seed_from_u64(13);
+        let X: Array2<f64> = Array::random_using((60, 12), Uniform::new(-1., 1.), &mut rng);
+
+        // without penalty the components span the principal subspace
+        let dense = SparsePCA::fit(SparsePCAHyperParams::new(3).alpha(0.0).max_n_iterations(5000).build(), &X);
+        let pca = PCA::fit(PCAHyperParams::new(Components::Count(3)).build(), &X);
+        let V = dense.components();
+        let projector = V.t().dot(&V.dot(&V.t()).inv().unwrap()).dot(V);
+        let expected = pca.components().t().dot(pca.components());
+        assert!((projector - expected).iter().all(|x| x.abs() < 1e-4));
+        assert!(dense.sparsity().iter().all(|&s| s == 0.0));
+
+        // and the sparsity grows with alpha
+        let mut previous = 0.0;
+        for &alpha in &[0.5, 2.0, 5.0] {
+            let model = SparsePCA::fit(SparsePCAHyperParams::new(3).alpha(alpha).build(), &X);
+            let sparsity = model.sparsity().sum();
+            assert!(sparsity >= previous);
+            previous = sparsity;
+        }
+        assert!(previous > 0.0);
+    }
+
+    #[test]
+    fn test_SparsePCA_zero_components() {
+        // a penalty this large zeroes every loading
+        let mut rng = StdRng::seed_from_u64(29);
+        let X: Array2<f64> = Array::random_using((30, 5), Uniform::new(-1., 1.), &mut rng);
+        let model = SparsePCA::fit(SparsePCAHyperParams::new(2).alpha(1e6).build(), &X);
+        assert!(model.components().iter().all(|&x| x == 0.0));
+
+        // the codes are then zero and the reconstruction is the mean
+        let Z = model.try_transform(&X).unwrap();
+        assert!(Z.iter().all(|&x| x == 0.0));
+        let reconstruction = model.inverse_transform(&Z);
+        assert!((reconstruction - model.mean()).iter().all(|&x| x == 0.0));
+    }
+
+    #[test]
+    fn test_SparsePCA_errors() {
+        let X: Array2<f64> = Array::random((10, 3), Uniform::new(-1., 1.));
+        assert!(matches!(
+            SparsePCA::try_fit(SparsePCAHyperParams::new(4).build(), &X),
+            Err(Error::TooManyComponents { requested: 4, max: 3 })
+        ));
+
+        // new samples and codes must have the width seen during fit
+        let model = SparsePCA::fit(SparsePCAHyperParams::new(2).build(), &X);
+        assert!(matches!(
+            model.try_transform(&X.slice(s![.., ..2])),
+            Err(Error::FeatureMismatch { expected: 3, found: 2 })
+        ));
+        assert!(matches!(
+            model.try_inverse_transform(&Array2::zeros((4, 3))),
+            Err(Error::ComponentMismatch { expected: 2, found: 3 })
+        ));
+
+        assert!(matches!(
+            SparsePCAHyperParams::new(2).alpha(-1.0).try_build(),
+            Err(Error::InvalidHyperParameter(_))
+        ));
+        assert!(matches!(
+            SparsePCAHyperParams::new(2).ridge_alpha(0.0).try_build(),
+            Err(Error::InvalidHyperParameter(_))
+        ));
+    }
+}
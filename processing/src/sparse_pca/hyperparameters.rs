@@ -0,0 +1,146 @@
+#![allow(non_snake_case, non_camel_case_types)]
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SparsePCAHyperParams {
+    /// n_components - number of sparse components to extract
+    pub(crate) n_components: usize,
+    /// alpha - l1 penalty on the loadings, default = 1
+    pub(crate) alpha: f64,
+    /// ridge_alpha - ridge penalty of the regression used by `transform`, default = 0.01
+    pub(crate) ridge_alpha: f64,
+    /// tolerance - relative decrease of the objective to stop at, default = 1e-8
+    pub(crate) tolerance: f64,
+    /// max_n_iterations - maximum number of iterations, default = 1000
+    pub(crate) max_n_iterations: u64,
+}
+
+/// An helper struct used to construct a set of [valid hyperparameters]
+pub struct SparsePCAHyperParamsBuilder {
+    n_components: usize,
+    alpha: f64,
+    ridge_alpha: f64,
+    tolerance: f64,
+    max_n_iterations: u64,
+}
+
+impl SparsePCAHyperParamsBuilder {
+
+    /// Set the value of `alpha`.
+    ///
+    /// The larger alpha, the more loadings are exactly zero
+    pub fn alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Set the value of `ridge_alpha`.
+    ///
+    /// The sparse components are not orthogonal, `transform` projects on them
+    /// with a ridge regression penalized by ridge_alpha, which must be positive
+    pub fn ridge_alpha(mut self, ridge_alpha: f64) -> Self {
+        self.ridge_alpha = ridge_alpha;
+        self
+    }
+
+    /// Set the value of `tolerance`.
+    ///
+    /// The training is considered complete once an iteration decreases the
+    /// objective by less than `tolerance` times its value
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Set the value of `max_n_iterations`.
+    ///
+    /// We exit the alternating minimization once the max_n_iterations have been
+    /// reached, even if the tolerance condition has not been met
+    pub fn max_n_iterations(mut self, max_n_iterations: u64) -> Self {
+        self.max_n_iterations = max_n_iterations;
+        self
+    }
+
+    /// Return an instance of `SparsePCAHyperParams` after
+    /// having performed validation checks on all the specified hyperparamters.
+    ///
+    /// **Panics** if any of the validation checks fails.
+    pub fn build(self) -> SparsePCAHyperParams {
+        match self.try_build() {
+            Ok(hyperparameters) => hyperparameters,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Return an instance of `SparsePCAHyperParams` after
+    /// having performed validation checks on all the specified hyperparamters,
+    /// or the first check that failed.
+    pub fn try_build(self) -> Result<SparsePCAHyperParams> {
+        SparsePCAHyperParams::build(self.n_components, self.alpha, self.ridge_alpha, self.tolerance, self.max_n_iterations)
+    }
+}
+
+impl SparsePCAHyperParams {
+    /// `new` lets us configure our sparse PCA parameters:
+    /// * `n_components` sparse components are extracted by `fit`.
+    ///
+    /// `n_components` is mandatory.
+    ///
+    /// Defaults are provided if optional parameters are not specified:
+    /// * `alpha = 1`;
+    /// * `ridge_alpha = 0.01`;
+    /// * `tolerance = 1e-8`;
+    /// * `max_n_iterations = 1000`.
+    pub fn new(n_components: usize) -> SparsePCAHyperParamsBuilder {
+        SparsePCAHyperParamsBuilder {
+            n_components,
+            alpha: 1.0,
+            ridge_alpha: 0.01,
+            tolerance: 1.0e-8,
+            max_n_iterations: 1000,
+        }
+    }
+
+    /// The number of sparse components extracted by `fit`.
+    pub fn n_components(&self) -> usize { self.n_components }
+
+    /// The l1 penalty on the loadings.
+    pub fn alpha(&self) -> f64 { self.alpha }
+
+    /// The ridge penalty of the regression used by `transform`.
+    pub fn ridge_alpha(&self) -> f64 { self.ridge_alpha }
+
+    /// The relative decrease of the objective to stop at.
+    pub fn tolerance(&self) -> f64 { self.tolerance }
+
+    /// The maximum number of iterations.
+    pub fn max_n_iterations(&self) -> u64 { self.max_n_iterations }
+
+    fn build(n_components: usize, alpha: f64, ridge_alpha: f64, tolerance: f64, max_n_iterations: u64) -> Result<Self> {
+        // the shape dependent checks happen in `fit`
+        if n_components == 0 {
+            return Err(Error::InvalidHyperParameter("`n_components` cannot be 0!".to_string()));
+        }
+        if !alpha.is_finite() || alpha < 0.0 {
+            return Err(Error::InvalidHyperParameter("`alpha` must be non-negative!".to_string()));
+        }
+        // a zero component makes the unpenalized gram matrix singular
+        if !ridge_alpha.is_finite() || ridge_alpha <= 0.0 {
+            return Err(Error::InvalidHyperParameter("`ridge_alpha` must be greater than 0!".to_string()));
+        }
+        if tolerance.is_nan() || tolerance <= 0. {
+            return Err(Error::InvalidHyperParameter("`tolerance` must be greater than 0!".to_string()));
+        }
+        if max_n_iterations == 0 {
+            return Err(Error::InvalidHyperParameter("`max_n_iterations` cannot be 0!".to_string()));
+        }
+        Ok(SparsePCAHyperParams {
+            n_components,
+            alpha,
+            ridge_alpha,
+            tolerance,
+            max_n_iterations,
+        })
+    }
+}
@@ -1,3 +1,5 @@
+#![allow(non_snake_case)]
+extern crate processing;
 
 /// Processing algorithms for components analysis/ dimensionality reduction
 pub mod processing_demo {